@@ -0,0 +1,420 @@
+//! This module provides support for aggregating file sizes into summary reports.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::record::FileRecord;
+use crate::summary::LargestFile;
+
+/// `DirectorySizes` accumulates cumulative directory size totals during a file system walk.
+///
+/// Every file size that is added is rolled up into each ancestor directory of the file
+/// path, up to and including the walk root directory of the file.  The result is a `du`-style report
+/// of the total size of the files that are found under each directory.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::{Path, PathBuf};
+///
+/// use siz::aggregate::DirectorySizes;
+///
+/// let mut dir_sizes = DirectorySizes::new(&[PathBuf::from("root")]);
+/// dir_sizes.add_directory(Path::new("root"));
+/// dir_sizes.add_directory(Path::new("root/a"));
+/// dir_sizes.add_file(Path::new("root/a/one.txt"), 10);
+/// dir_sizes.add_file(Path::new("root/two.txt"), 5);
+///
+/// let mut totals = dir_sizes.into_vec(None);
+/// totals.sort();
+/// assert_eq!(
+///     totals,
+///     vec![(10, PathBuf::from("root/a")), (15, PathBuf::from("root"))]
+/// );
+/// ```
+pub struct DirectorySizes {
+    roots: Vec<PathBuf>,
+    sizes: HashMap<PathBuf, u64>,
+}
+
+impl DirectorySizes {
+    /// Creates a new, empty `DirectorySizes` instance for the walk root directories `roots`.
+    pub fn new(roots: &[PathBuf]) -> Self {
+        Self {
+            roots: roots.to_vec(),
+            sizes: HashMap::new(),
+        }
+    }
+
+    /// Registers a directory path so that it is reported even when it does not
+    /// contain any files.
+    #[inline(always)]
+    pub fn add_directory(&mut self, dirpath: &Path) {
+        if !self.sizes.contains_key(dirpath) {
+            self.sizes.insert(dirpath.to_path_buf(), 0);
+        }
+    }
+
+    /// Adds the `filesize` of the file at `filepath` to the totals of every ancestor
+    /// directory of the file path, up to and including the first walk root directory that
+    /// contains the file.  A file that is a walk root path is reported as its own entry.
+    #[inline(always)]
+    pub fn add_file(&mut self, filepath: &Path, filesize: u64) {
+        let Some(root) = root_of(&self.roots, filepath) else {
+            return;
+        };
+        if filepath == root {
+            *self.sizes.entry(filepath.to_path_buf()).or_insert(0) += filesize;
+            return;
+        }
+        for ancestor in filepath.ancestors().skip(1) {
+            if !ancestor.starts_with(root) {
+                break;
+            }
+            // the walker yields parent directories before their files so the
+            // lookup will typically succeed without an allocation here
+            match self.sizes.get_mut(ancestor) {
+                Some(total) => *total += filesize,
+                None => {
+                    self.sizes.insert(ancestor.to_path_buf(), filesize);
+                }
+            }
+        }
+    }
+
+    /// Consumes the `DirectorySizes` and returns a vector of (total size, directory path)
+    /// tuples in arbitrary order.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_depth` - An optional maximum display depth relative to the walk root directories.
+    ///   The walk root directory is at depth 0.  Directories below this depth are excluded from
+    ///   the returned vector, but their sizes remain included in the totals of their ancestors.
+    pub fn into_vec(self, max_depth: Option<usize>) -> Vec<(u64, PathBuf)> {
+        let roots = self.roots;
+        self.sizes
+            .into_iter()
+            .filter(|(dirpath, _)| match max_depth {
+                Some(depth) => root_of(&roots, dirpath)
+                    .and_then(|root| dirpath.strip_prefix(root).ok())
+                    .is_some_and(|relpath| relpath.components().count() <= depth),
+                None => true,
+            })
+            .map(|(dirpath, total)| (total, dirpath))
+            .collect()
+    }
+}
+
+/// Returns the first walk root directory of `roots` that contains `path`.
+#[inline(always)]
+fn root_of<'r>(roots: &'r [PathBuf], path: &Path) -> Option<&'r PathBuf> {
+    roots.iter().find(|root| path.starts_with(root))
+}
+
+/// Returns the extension of the file name of `path`, or `None` when the file name does
+/// not have an extension.
+///
+/// The extension includes up to `parts` trailing dot-separated parts of the file name,
+/// e.g., `gz` with one part and `tar.gz` with two parts for `archive.tar.gz`.  The file
+/// stem is never included, and the leading dot of a hidden file is not an extension
+/// separator.
+///
+/// # Parameters
+///
+/// * `path`: The file path.
+/// * `parts`: The maximum number of extension parts.
+/// * `fold_case`: A boolean that indicates whether to convert the extension to lowercase.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+///
+/// use siz::aggregate::file_extension;
+///
+/// let path = Path::new("dist/archive.TAR.gz");
+/// assert_eq!(file_extension(path, 1, false), Some("gz".to_string()));
+/// assert_eq!(file_extension(path, 2, false), Some("TAR.gz".to_string()));
+/// assert_eq!(file_extension(path, 2, true), Some("tar.gz".to_string()));
+/// assert_eq!(file_extension(Path::new(".bashrc"), 1, false), None);
+/// ```
+pub fn file_extension(path: &Path, parts: usize, fold_case: bool) -> Option<String> {
+    let file_name = path.file_name()?.to_string_lossy();
+    let file_name = file_name.strip_prefix('.').unwrap_or(&file_name);
+    let segments: Vec<&str> = file_name.split('.').collect();
+    // the first segment is the file stem
+    let start = segments.len().saturating_sub(parts).max(1);
+    if start >= segments.len() {
+        return None;
+    }
+    let extension = segments[start..].join(".");
+    if extension.is_empty() {
+        None
+    } else if fold_case {
+        Some(extension.to_lowercase())
+    } else {
+        Some(extension)
+    }
+}
+
+/// `GroupTotal` is a struct that represents the file count and total size of a group
+/// of files in an aggregate report.
+///
+/// # Fields
+///
+/// * `name`: The group name, e.g., a file type alias name.
+/// * `files`: The number of files in the group.
+/// * `total`: The total size of the files in the group in bytes.
+/// * `largest`: The largest file in the group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupTotal {
+    pub name: String,
+    pub files: usize,
+    pub total: u64,
+    pub largest: LargestFile,
+}
+
+impl GroupTotal {
+    /// Returns the share of the group total in the `grand_total` size as a percentage.
+    #[inline(always)]
+    pub fn share(&self, grand_total: u64) -> f64 {
+        if grand_total == 0 {
+            0.0
+        } else {
+            self.total as f64 / grand_total as f64 * 100.0
+        }
+    }
+}
+
+/// `GroupSizes` accumulates the file counts and total sizes of named groups of files
+/// during a file system walk.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+///
+/// use siz::aggregate::GroupSizes;
+/// use siz::record::FileRecord;
+///
+/// let mut group_sizes = GroupSizes::default();
+/// group_sizes.add("rust", &FileRecord::from_size(10, PathBuf::from("main.rs")));
+/// group_sizes.add("rust", &FileRecord::from_size(30, PathBuf::from("lib.rs")));
+/// group_sizes.add("unknown", &FileRecord::from_size(60, PathBuf::from("data")));
+/// assert_eq!(group_sizes.total(), 100);
+///
+/// let mut groups = group_sizes.into_vec();
+/// groups.sort_by(|a, b| a.name.cmp(&b.name));
+/// assert_eq!(groups[0].files, 2);
+/// assert_eq!(groups[0].total, 40);
+/// assert_eq!(groups[0].share(100), 40.0);
+/// ```
+#[derive(Debug, Default)]
+pub struct GroupSizes {
+    groups: HashMap<String, GroupTotal>,
+    total: u64,
+}
+
+impl GroupSizes {
+    /// Adds a file record to the group `name`.
+    #[inline(always)]
+    pub fn add(&mut self, name: &str, record: &FileRecord) {
+        self.total += record.size;
+        match self.groups.get_mut(name) {
+            Some(group) => {
+                group.files += 1;
+                group.total += record.size;
+                if record.size > group.largest.size {
+                    group.largest = LargestFile {
+                        path: record.path.clone(),
+                        size: record.size,
+                    };
+                }
+            }
+            None => {
+                self.groups.insert(
+                    name.to_string(),
+                    GroupTotal {
+                        name: name.to_string(),
+                        files: 1,
+                        total: record.size,
+                        largest: LargestFile {
+                            path: record.path.clone(),
+                            size: record.size,
+                        },
+                    },
+                );
+            }
+        }
+    }
+
+    /// Returns the total size of all files in all groups in bytes.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Consumes the `GroupSizes` instance and returns the group totals in an
+    /// unspecified order.
+    pub fn into_vec(self) -> Vec<GroupTotal> {
+        self.groups.into_values().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn build_dir_sizes() -> DirectorySizes {
+        let mut dir_sizes = DirectorySizes::new(&[PathBuf::from("r")]);
+        dir_sizes.add_directory(Path::new("r"));
+        dir_sizes.add_directory(Path::new("r/a"));
+        dir_sizes.add_directory(Path::new("r/a/b"));
+        dir_sizes.add_directory(Path::new("r/empty"));
+        dir_sizes.add_file(Path::new("r/one.txt"), 1);
+        dir_sizes.add_file(Path::new("r/a/two.txt"), 20);
+        dir_sizes.add_file(Path::new("r/a/b/three.txt"), 300);
+        dir_sizes.add_file(Path::new("r/a/b/four.txt"), 4000);
+        dir_sizes
+    }
+
+    #[test]
+    fn test_directory_sizes_totals() {
+        let mut totals = build_dir_sizes().into_vec(None);
+        totals.sort();
+        assert_eq!(
+            totals,
+            vec![
+                (0, PathBuf::from("r/empty")),
+                (4300, PathBuf::from("r/a/b")),
+                (4320, PathBuf::from("r/a")),
+                (4321, PathBuf::from("r")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_directory_sizes_display_depth() {
+        let mut totals = build_dir_sizes().into_vec(Some(1));
+        totals.sort();
+        // r/a/b is excluded from the report, but its
+        // files are included in the r/a and r totals
+        assert_eq!(
+            totals,
+            vec![
+                (0, PathBuf::from("r/empty")),
+                (4320, PathBuf::from("r/a")),
+                (4321, PathBuf::from("r")),
+            ]
+        );
+
+        let totals = build_dir_sizes().into_vec(Some(0));
+        assert_eq!(totals, vec![(4321, PathBuf::from("r"))]);
+    }
+
+    #[test]
+    fn test_directory_sizes_unregistered_ancestors() {
+        // ancestor directories are created on demand when
+        // they were not registered before a file was added
+        let mut dir_sizes = DirectorySizes::new(&[PathBuf::from("r")]);
+        dir_sizes.add_file(Path::new("r/a/one.txt"), 5);
+        let mut totals = dir_sizes.into_vec(None);
+        totals.sort();
+        assert_eq!(
+            totals,
+            vec![(5, PathBuf::from("r")), (5, PathBuf::from("r/a"))]
+        );
+    }
+
+    #[test]
+    fn test_directory_sizes_multiple_roots() {
+        // each file is rolled up to its own root directory, and the
+        // display depth is relative to the root of each directory
+        let mut dir_sizes = DirectorySizes::new(&[PathBuf::from("r"), PathBuf::from("s")]);
+        dir_sizes.add_file(Path::new("r/a/one.txt"), 5);
+        dir_sizes.add_file(Path::new("s/two.txt"), 7);
+        dir_sizes.add_file(Path::new("t/three.txt"), 9);
+        let mut totals = dir_sizes.into_vec(Some(0));
+        totals.sort();
+        assert_eq!(
+            totals,
+            vec![(5, PathBuf::from("r")), (7, PathBuf::from("s"))]
+        );
+    }
+
+    #[test]
+    fn test_directory_sizes_file_root() {
+        // a file path argument is reported as its own entry at depth 0
+        let mut dir_sizes = DirectorySizes::new(&[PathBuf::from("r"), PathBuf::from("f.txt")]);
+        dir_sizes.add_directory(Path::new("r"));
+        dir_sizes.add_file(Path::new("r/one.txt"), 5);
+        dir_sizes.add_file(Path::new("f.txt"), 7);
+        let mut totals = dir_sizes.into_vec(Some(0));
+        totals.sort();
+        assert_eq!(
+            totals,
+            vec![(5, PathBuf::from("r")), (7, PathBuf::from("f.txt"))]
+        );
+    }
+
+    #[test]
+    fn test_group_sizes() {
+        let mut group_sizes = GroupSizes::default();
+        for (group, size, path) in [
+            ("rust", 10, "a.rs"),
+            ("unknown", 5, "b"),
+            ("rust", 40, "c.rs"),
+            ("rust", 40, "d.rs"),
+        ] {
+            group_sizes.add(group, &FileRecord::from_size(size, PathBuf::from(path)));
+        }
+        assert_eq!(group_sizes.total(), 95);
+        let mut groups = group_sizes.into_vec();
+        groups.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(
+            groups,
+            vec![
+                GroupTotal {
+                    name: "rust".to_string(),
+                    files: 3,
+                    total: 90,
+                    // the first of the largest files is retained
+                    largest: LargestFile {
+                        path: PathBuf::from("c.rs"),
+                        size: 40
+                    },
+                },
+                GroupTotal {
+                    name: "unknown".to_string(),
+                    files: 1,
+                    total: 5,
+                    largest: LargestFile {
+                        path: PathBuf::from("b"),
+                        size: 5
+                    },
+                },
+            ]
+        );
+        assert_eq!(groups[1].share(100), 5.0);
+        assert_eq!(groups[1].share(0), 0.0);
+    }
+
+    #[test]
+    fn test_file_extension() {
+        let ext = |path: &str, parts: usize, fold_case: bool| {
+            file_extension(Path::new(path), parts, fold_case)
+        };
+        assert_eq!(ext("a/b.rs", 1, false), Some("rs".to_string()));
+        assert_eq!(ext("a/b.rs", 3, false), Some("rs".to_string()));
+        assert_eq!(ext("IMG.JPG", 1, false), Some("JPG".to_string()));
+        assert_eq!(ext("IMG.JPG", 1, true), Some("jpg".to_string()));
+        assert_eq!(ext("a.tar.gz", 1, false), Some("gz".to_string()));
+        assert_eq!(ext("a.tar.gz", 2, false), Some("tar.gz".to_string()));
+        assert_eq!(ext("jquery.min.js", 2, false), Some("min.js".to_string()));
+        assert_eq!(ext(".config.toml", 1, false), Some("toml".to_string()));
+        assert_eq!(ext(".config.toml", 2, false), Some("toml".to_string()));
+        assert_eq!(ext("Makefile", 1, false), None);
+        assert_eq!(ext(".gitignore", 1, false), None);
+        assert_eq!(ext("trailing.", 1, false), None);
+        assert_eq!(ext("a.rs", 0, false), None);
+    }
+}
@@ -1,8 +1,13 @@
 //! This module provides structures and functions for parsing command line arguments.
 use std::path::PathBuf;
+use std::time::SystemTime;
 
 use clap::builder::styling::{AnsiColor, Effects, Styles};
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+
+use crate::budget::{parse_budget_limit, BudgetLimit};
+use crate::filter::{parse_size, parse_time};
+use crate::types::{parse_type_definition, TypeDefinition};
 
 fn styles() -> Styles {
     Styles::styled()
@@ -12,43 +17,195 @@ fn styles() -> Styles {
         .placeholder(AnsiColor::Green.on_default())
 }
 
+/// `OutputFormat` is an enum that represents the supported report output formats.
+///
+/// # Variants
+///
+/// * `Text`: Tab-delimited size and path lines.
+/// * `Json`: A JSON array of file record objects.
+/// * `Ndjson`: Newline-delimited JSON file record objects, one object per line.
+/// * `Csv`: RFC 4180 comma-separated values with a header row.
+/// * `Tsv`: RFC 4180-quoted tab-separated values with a header row.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Ndjson,
+    Csv,
+    Tsv,
+}
+
+/// `Column` is an enum that represents the columns that are available in the
+/// CSV and TSV output formats.
+///
+/// # Variants
+///
+/// * `Bytes`: The size in bytes.
+/// * `Size`: The size in human-readable units.
+/// * `Allocated`: The allocated disk usage in bytes.
+/// * `Path`: The file path.
+/// * `Ext`: The file extension.
+/// * `Type`: The built-in file type alias name.
+/// * `Mtime`: The last modification time as an RFC 3339 UTC timestamp.
+/// * `Link`: Whether the file is a duplicate hard link to a previously reported file.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Column {
+    Bytes,
+    Size,
+    Allocated,
+    Path,
+    #[value(alias = "extension")]
+    Ext,
+    Type,
+    Mtime,
+    Link,
+}
+
+impl Column {
+    /// Returns the header row name of the column.
+    pub fn header(&self) -> &'static str {
+        match self {
+            Column::Bytes => "bytes",
+            Column::Size => "size",
+            Column::Allocated => "allocated",
+            Column::Path => "path",
+            Column::Ext => "extension",
+            Column::Type => "type",
+            Column::Mtime => "mtime",
+            Column::Link => "duplicate_link",
+        }
+    }
+}
+
+/// `LinkCount` is an enum that represents how files with multiple hard links are counted.
+///
+/// # Variants
+///
+/// * `Once`: Report the first link to a file only, and suppress the duplicate links.
+/// * `All`: Report every link to a file, and mark the duplicate links.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkCount {
+    Once,
+    All,
+}
+
 /// `Args` is a struct that represents the command line arguments passed to the program.
 ///
 /// It uses the `clap::Parser` derive macro to parse the command line arguments.
 ///
 /// # Fields
 ///
-/// * `path`: An optional `PathBuf` that represents the file or directory path.
+/// * `paths`: A vector of `PathBuf` that represents the file and directory paths.
 ///
 /// * `binary_units`: A boolean that indicates whether to display the size in human readable binary units.
 ///
+/// * `both_sizes`: A boolean that indicates whether to display both the apparent size and the allocated disk usage in the text output format.
+///
+/// * `bottom`: An optional `usize` that represents the number of smallest files to report.
+///
+/// * `budget`: An optional `PathBuf` that represents the size budget rules file path.
+///
+/// * `budget_each`: A vector of `BudgetLimit` that represents the size limits of each matching file.
+///
+/// * `budget_total`: A vector of `BudgetLimit` that represents the total size limits of the matching files.
+///
+/// * `by_extension`: A boolean that indicates whether to report the total size of the files of each file extension.
+///
+/// * `by_ignore`: A boolean that indicates whether to report the total size of the ignored and the tracked files.
+///
+/// * `by_type`: A boolean that indicates whether to report the total size of the files of each file type alias.
+///
 /// * `color`: A boolean that indicates whether to use ANSI colored output.
 ///
+/// * `columns`: A vector of `Column` that represents the CSV and TSV output format columns.
+///
+/// * `count_links`: An optional `LinkCount` that represents how files with multiple hard links are counted.
+///
 /// * `depth`: An optional `usize` that represents the maximum directory traversal depth.
 ///
+/// * `diff`: An optional vector of the old and new `PathBuf` snapshot file or directory paths to compare.
+///
+/// * `dirs`: A boolean that indicates whether to report cumulative directory size totals.
+///
+/// * `disk_usage`: A boolean that indicates whether to report the allocated disk usage instead of the apparent size.
+///
+/// * `duplicates`: A boolean that indicates whether to report the sets of files with identical contents.
+///
+/// * `exclude`: A vector of `String` that represents gitignore syntax glob patterns of the paths to exclude from the output.
+///
+/// * `exclude_from`: A vector of `PathBuf` that represents the files of exclude glob patterns.
+///
+/// * `extension_parts`: A `usize` that represents the maximum number of dot-separated parts in a file extension.
+///
+/// * `files_from`: An optional `PathBuf` that represents the file list path of the files to report instead of a directory walk.
+///
+/// * `fold_case`: A boolean that indicates whether to fold the case of file extensions.
+///
 /// * `follow`: A boolean that indicates whether to follow symbolic links.
 ///
+/// * `from0`: A boolean that indicates whether the file list paths are NUL-delimited.
+///
 /// * `glob`: An optional vector of `String` that represents path glob patterns to filter the output.
 ///
 /// * `hidden`: A boolean that indicates whether to show hidden dot files and dot directories.
 ///
 /// * `highlow`: A boolean that indicates whether to sort the output by largest to smallest file size.
 ///
+/// * `ignore_file`: A vector of `PathBuf` that represents the gitignore syntax ignore files to respect.
+///
 /// * `list-types`: A boolean that indicates whether to print the built-in types available for use with the types filter.
 ///
+/// * `max_size`: An optional `u64` that represents the maximum file size in bytes.
+///
 /// * `metric_units`: A boolean that indicates whether to display the size in human readable SI metric units.
 ///
+/// * `min_size`: An optional `u64` that represents the minimum file size in bytes.
+///
+/// * `mtime`: A boolean that indicates whether to display the file modification time in the text output format.
+///
 /// * `name`: A boolean that indicates whether to sort the output by path name.
 ///
+/// * `newer_than`: An optional `SystemTime` that represents the modification time that files must be newer than.
+///
+/// * `no_config`: A boolean that indicates whether to disable the siz.toml configuration files.
+///
+/// * `no_ignore`: A boolean that indicates whether to disable the .gitignore and .ignore file rules.
+///
+/// * `no_ignore_vcs`: A boolean that indicates whether to disable the .gitignore file rules.
+///
+/// * `older_than`: An optional `SystemTime` that represents the modification time that files must be older than.
+///
+/// * `one_file_system`: A boolean that indicates whether to skip directories on other file systems.
+///
+/// * `only_ignored`: A boolean that indicates whether to report only the files that the ignore file rules exclude.
+///
+/// * `output`: An `OutputFormat` that represents the report output format.
+///
 /// * `parallel`: A boolean that indicates whether to use parallel recursive directory walk (non-deterministic order).
 ///
+/// * `paths_only`: A boolean that indicates whether to write only the paths of the report records in the text output format.
+///
+/// * `print0`: A boolean that indicates whether to terminate the report records with NUL instead of newline in the text output format.
+///
+/// * `save_snapshot`: An optional `PathBuf` that represents the snapshot file path of the report files.
+///
+/// * `summary`: A boolean that indicates whether to write a summary of the report statistics.
+///
+/// * `top`: An optional `usize` that represents the number of largest files to report.
+///
 /// * `default_type`: An optional vector of `String` that represents file type names to filter the output.
-#[derive(Parser, Debug)]
+///
+/// * `type_add`: A vector of `TypeDefinition` that represents the user-defined file type aliases.
+///
+/// * `type_not`: An optional vector of `String` that represents file type names to exclude from the output.
+#[derive(Parser, Clone, Debug)]
 #[command(name = "siz")]
-#[command(author, version, about, styles = styles(), long_about = None, override_usage = "siz [COMMAND] [OPTIONS] PATH")]
+#[command(author, version, about, styles = styles(), long_about = None, override_usage = "siz [OPTIONS] PATH...\n       siz [OPTIONS] --diff OLD NEW")]
 pub struct Args {
-    /// File or directory path
-    pub path: Option<PathBuf>,
+    /// File or directory paths
+    #[arg(value_name = "PATH")]
+    pub paths: Vec<PathBuf>,
 
     /// Size in human-readable binary units
     #[arg(
@@ -60,26 +217,221 @@ pub struct Args {
     )]
     pub binary_units: bool,
 
+    /// Display both the apparent size and the allocated disk usage columns
+    /// in the text output format
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with = "disk_usage",
+        conflicts_with = "dirs",
+        help_heading = "Size Formats"
+    )]
+    pub both_sizes: bool,
+
+    /// Report the N smallest files only
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with = "top",
+        help_heading = "Filters"
+    )]
+    pub bottom: Option<usize>,
+
+    /// Assert the size budget rules of a budget file. Each line defines a
+    /// 'total PATTERN SIZE' or 'each PATTERN SIZE' rule. Budget violations
+    /// are reported with exit status code 3.
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["by_extension", "by_ignore", "by_type", "dirs", "duplicates", "summary", "top", "bottom", "parallel", "save_snapshot"],
+        help_heading = "Budgets"
+    )]
+    pub budget: Option<PathBuf>,
+
+    /// Assert that each file that matches a glob PATTERN is not larger than
+    /// SIZE, e.g., '**=10MB'. Budget violations are reported with exit
+    /// status code 3.
+    #[arg(
+        long,
+        value_name = "PATTERN=SIZE",
+        value_parser = parse_budget_limit,
+        conflicts_with_all = ["by_extension", "by_ignore", "by_type", "dirs", "duplicates", "summary", "top", "bottom", "parallel", "save_snapshot"],
+        help_heading = "Budgets"
+    )]
+    pub budget_each: Vec<BudgetLimit>,
+
+    /// Assert that the total size of the files that match a glob PATTERN is
+    /// not larger than SIZE, e.g., 'dist/**/*.js=500KiB'. Budget violations
+    /// are reported with exit status code 3.
+    #[arg(
+        long,
+        value_name = "PATTERN=SIZE",
+        value_parser = parse_budget_limit,
+        conflicts_with_all = ["by_extension", "by_ignore", "by_type", "dirs", "duplicates", "summary", "top", "bottom", "parallel", "save_snapshot"],
+        help_heading = "Budgets"
+    )]
+    pub budget_total: Vec<BudgetLimit>,
+
+    /// Report the file count, total size, share of the total size, and largest file of
+    /// each file extension. Files without an extension are reported as (none).
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with_all = ["by_type", "dirs", "summary", "top", "bottom", "parallel"],
+        help_heading = "Reports"
+    )]
+    pub by_extension: bool,
+
+    /// Report the file count, total size, share of the total size, and largest
+    /// files of the files that the .gitignore, .ignore, and ignore-file rules
+    /// exclude (ignored) and the other files (tracked). Use the top option to
+    /// set the number of largest files of each class (default: 10).
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with_all = ["by_extension", "by_type", "dirs", "duplicates", "summary", "bottom", "parallel", "save_snapshot", "no_ignore", "only_ignored"],
+        help_heading = "Reports"
+    )]
+    pub by_ignore: bool,
+
+    /// Report the file count, total size, and share of the total size of each built-in
+    /// file type alias. Files that do not match a type are reported as unknown.
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with_all = ["dirs", "summary", "top", "bottom", "parallel"],
+        help_heading = "Reports"
+    )]
+    pub by_type: bool,
+
     /// ANSI colored output
     #[arg(short, long, default_value_t = false, help_heading = "Color")]
     pub color: bool,
 
+    /// Comma-separated list of columns in the CSV and TSV output formats
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = [Column::Bytes, Column::Path],
+        help_heading = "Output Formats"
+    )]
+    pub columns: Vec<Column>,
+
+    /// Detect files with multiple hard links (and files that are reached through
+    /// followed symbolic links). Count them once and suppress the duplicate links,
+    /// or report all links and mark the duplicates. Unix only.
+    #[arg(long, value_enum, value_name = "MODE", help_heading = "Filters")]
+    pub count_links: Option<LinkCount>,
+
     /// Maximum directory traversal depth
     #[arg(short, long, help_heading = "Filters")]
     pub depth: Option<usize>,
 
+    /// Report the added, removed, grown, and shrunk files between two runs.
+    /// OLD and NEW are snapshot files that were written with the
+    /// save-snapshot option or directory paths that are walked live.
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["OLD", "NEW"],
+        conflicts_with_all = ["paths", "budget", "budget_each", "budget_total", "by_extension", "by_ignore", "by_type", "dirs", "duplicates", "summary", "parallel", "top", "bottom", "save_snapshot", "files_from", "print0", "paths_only"],
+        help_heading = "Reports"
+    )]
+    pub diff: Option<Vec<PathBuf>>,
+
+    /// Report cumulative directory size totals. The depth option
+    /// defines the maximum report display depth in this mode.
+    #[arg(
+        short = 'D',
+        long,
+        default_value_t = false,
+        conflicts_with = "parallel",
+        help_heading = "Reports"
+    )]
+    pub dirs: bool,
+
+    /// Report the allocated disk usage (st_blocks * 512 on Unix) instead of the apparent
+    /// file size. Sorting, size filters, and totals use the disk usage in this mode.
+    #[arg(
+        short = 'u',
+        long,
+        default_value_t = false,
+        help_heading = "Size Formats"
+    )]
+    pub disk_usage: bool,
+
+    /// Report sets of files with identical contents and the reclaimable size.
+    /// Files are compared by size, then a partial hash, then a full hash.
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with_all = ["by_extension", "by_type", "dirs", "summary", "top", "bottom", "parallel"],
+        help_heading = "Reports"
+    )]
+    pub duplicates: bool,
+
+    /// Exclude the paths that match a gitignore syntax glob PATTERN from the
+    /// output. Excluded directories are not traversed. Repeat the option to
+    /// exclude more patterns.
+    #[arg(long, value_name = "PATTERN", help_heading = "Filters")]
+    pub exclude: Vec<String>,
+
+    /// Exclude the paths that match the gitignore syntax glob patterns of a
+    /// file, one pattern per line. Lines that start with '#' are comments.
+    #[arg(long, value_name = "FILE", help_heading = "Filters")]
+    pub exclude_from: Vec<PathBuf>,
+
+    /// Maximum number of dot-separated parts in the file extensions of the
+    /// by-extension report, e.g., 2 to group archive.tar.gz files as tar.gz
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+        requires = "by_extension",
+        help_heading = "Reports"
+    )]
+    pub extension_parts: usize,
+
+    /// Report the files of a file list instead of walking the PATH arguments,
+    /// one path per line. Use '-' to read the file list from standard input,
+    /// e.g., the output of git ls-files.
+    #[arg(
+        long,
+        value_name = "FILE|-",
+        conflicts_with_all = ["paths", "budget", "budget_each", "budget_total", "by_extension", "by_ignore", "by_type", "dirs", "duplicates", "parallel", "save_snapshot", "depth", "glob", "default_type", "type_not", "exclude", "exclude_from", "ignore_file", "no_ignore", "no_ignore_vcs", "only_ignored", "one_file_system"],
+        help_heading = "Input"
+    )]
+    pub files_from: Option<PathBuf>,
+
+    /// Fold the case of the file extensions in the by-extension report,
+    /// e.g., to group JPG and jpg files
+    #[arg(
+        long,
+        default_value_t = false,
+        requires = "by_extension",
+        help_heading = "Reports"
+    )]
+    pub fold_case: bool,
+
     /// Follow symbolic links
     #[arg(short = 'L', long, default_value_t = false, help_heading = "Filters")]
     pub follow: bool,
 
-    /// Filter the output by gitignore syntax glob patterns
+    /// Read NUL-delimited paths from the files-from file list, e.g., the
+    /// output of find -print0 or git ls-files -z
     #[arg(
-        short,
         long,
-        value_delimiter = ',',
-        conflicts_with = "default_type",
-        help_heading = "Filters"
+        default_value_t = false,
+        requires = "files_from",
+        conflicts_with = "paths",
+        help_heading = "Input"
     )]
+    pub from0: bool,
+
+    /// Filter the output by gitignore syntax glob patterns
+    #[arg(short, long, value_delimiter = ',', help_heading = "Filters")]
     pub glob: Option<Vec<String>>,
 
     /// Show hidden dot files and dot directories
@@ -94,16 +446,26 @@ pub struct Args {
         short = 'l',
         long,
         default_value_t = false,
-        conflicts_with = "parallel",
         conflicts_with = "name",
         help_heading = "Sorting"
     )]
     pub highlow: bool,
 
+    /// Respect the gitignore syntax ignore rules of a file, in addition to
+    /// the .gitignore and .ignore files. Patterns match paths relative to the
+    /// current working directory. Repeat the option to add more files.
+    #[arg(long, value_name = "PATH", help_heading = "Filters")]
+    pub ignore_file: Vec<PathBuf>,
+
     /// Print the built-in type aliases available for use with the type option
     #[arg(long = "list-types", default_value_t = false, help_heading = "Filters")]
     pub list_types: bool,
 
+    /// Filter the output to files that are smaller than or equal to a size,
+    /// e.g., 1GB or 500MiB. Supports SI metric and binary units.
+    #[arg(long, value_name = "SIZE", value_parser = parse_size, help_heading = "Filters")]
+    pub max_size: Option<u64>,
+
     /// Size in human-readable SI metric units
     #[arg(
         short,
@@ -114,17 +476,77 @@ pub struct Args {
     )]
     pub metric_units: bool,
 
+    /// Filter the output to files that are larger than or equal to a size,
+    /// e.g., 10MiB or 1kB. Supports SI metric and binary units.
+    #[arg(long, value_name = "SIZE", value_parser = parse_size, help_heading = "Filters")]
+    pub min_size: Option<u64>,
+
+    /// Display the file modification time (UTC) in the text output format
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with = "dirs",
+        help_heading = "Output Formats"
+    )]
+    pub mtime: bool,
+
     /// Sort by path name
     #[arg(
         short,
         long,
         default_value_t = false,
         conflicts_with = "highlow",
-        conflicts_with = "parallel",
         help_heading = "Sorting"
     )]
     pub name: bool,
 
+    /// Filter the output to files that were modified more recently than an age,
+    /// e.g., 6h or 30d, or a UTC date, e.g., 2024-01-31 or 2024-01-31T12:00:00.
+    #[arg(long, value_name = "AGE|DATE", value_parser = parse_time, help_heading = "Filters")]
+    pub newer_than: Option<SystemTime>,
+
+    /// Do not load the option defaults of the siz.toml configuration files
+    #[arg(long, default_value_t = false, help_heading = "Configuration")]
+    pub no_config: bool,
+
+    /// Do not respect the .gitignore and .ignore files
+    #[arg(long, default_value_t = false, help_heading = "Filters")]
+    pub no_ignore: bool,
+
+    /// Do not respect the .gitignore files
+    #[arg(long, default_value_t = false, help_heading = "Filters")]
+    pub no_ignore_vcs: bool,
+
+    /// Filter the output to files that were last modified before an age,
+    /// e.g., 90d or 1y, or a UTC date, e.g., 2024-01-31 or 2024-01-31T12:00:00.
+    #[arg(long, value_name = "AGE|DATE", value_parser = parse_time, help_heading = "Filters")]
+    pub older_than: Option<SystemTime>,
+
+    /// Stay on the file system of the PATH argument and skip mount points
+    /// of other file systems. Skipped mount points are reported on stderr.
+    #[arg(short = 'x', long, default_value_t = false, help_heading = "Filters")]
+    pub one_file_system: bool,
+
+    /// Report only the files that the .gitignore, .ignore, and ignore-file
+    /// rules exclude, e.g., to measure build output
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with = "no_ignore",
+        help_heading = "Filters"
+    )]
+    pub only_ignored: bool,
+
+    /// Report output format
+    #[arg(
+        short,
+        long,
+        value_enum,
+        default_value_t = OutputFormat::Text,
+        help_heading = "Output Formats"
+    )]
+    pub output: OutputFormat,
+
     /// Parallel recursive directory walk (non-deterministic order)
     #[arg(
         short,
@@ -136,18 +558,89 @@ pub struct Args {
     )]
     pub parallel: bool,
 
+    /// Write only the paths of the report records in the text output format
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with_all = ["budget", "budget_each", "budget_total", "by_extension", "by_ignore", "by_type", "duplicates", "summary"],
+        help_heading = "Output Formats"
+    )]
+    pub paths_only: bool,
+
+    /// Terminate the report records with NUL instead of newline in the text
+    /// output format, e.g., to pipe the paths-only output into xargs -0
+    #[arg(
+        short = '0',
+        long,
+        default_value_t = false,
+        conflicts_with_all = ["budget", "budget_each", "budget_total", "by_extension", "by_ignore", "by_type", "color", "duplicates", "summary"],
+        help_heading = "Output Formats"
+    )]
+    pub print0: bool,
+
+    /// Save the sizes, paths, and modification times of the report files to a
+    /// snapshot file for use with the diff option
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["by_extension", "by_type", "dirs", "duplicates", "top", "bottom", "parallel"],
+        help_heading = "Reports"
+    )]
+    pub save_snapshot: Option<PathBuf>,
+
+    /// Write a summary of the total size, file count, and file size statistics after the report
+    #[arg(
+        short,
+        long,
+        default_value_t = false,
+        conflicts_with = "dirs",
+        help_heading = "Reports"
+    )]
+    pub summary: bool,
+
+    /// Report the N largest files only
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with = "bottom",
+        help_heading = "Filters"
+    )]
+    pub top: Option<usize>,
+
     /// Filter the output by one or more comma-separated file type alias names.
     /// Use the list-types option to view a list of the built-in file type
-    /// aliases.
+    /// aliases. Combines with the glob option filters.
     #[arg(
         short = 't',
         long = "type",
         value_delimiter = ',',
         value_names = ["TY1,TY2,..."],
-        conflicts_with = "glob",
         help_heading = "Filters"
     )]
     pub default_type: Option<Vec<String>>,
+
+    /// Define a file type alias with comma-separated path glob patterns,
+    /// e.g., 'ml-weights:*.safetensors,*.ckpt,*.pt'. User-defined type
+    /// aliases are available in the type option, the by-type report, and
+    /// the list-types output.
+    #[arg(
+        long = "type-add",
+        value_name = "NAME:GLOB,...",
+        value_parser = parse_type_definition,
+        help_heading = "Filters"
+    )]
+    pub type_add: Vec<TypeDefinition>,
+
+    /// Exclude one or more comma-separated file type alias names from the
+    /// output. Combines with the type option selections.
+    #[arg(
+        short = 'T',
+        long = "type-not",
+        value_delimiter = ',',
+        value_names = ["TY1,TY2,..."],
+        help_heading = "Filters"
+    )]
+    pub type_not: Option<Vec<String>>,
 }
 
 impl Default for Args {
@@ -157,6 +650,7 @@ impl Default for Args {
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
 
@@ -164,19 +658,58 @@ mod tests {
     fn test_args_default_values() {
         let args = Args::default();
 
-        assert_eq!(args.path, None);
+        assert_eq!(args.paths, Vec::<PathBuf>::new());
         assert_eq!(args.binary_units, false);
+        assert_eq!(args.both_sizes, false);
+        assert_eq!(args.bottom, None);
+        assert_eq!(args.budget, None);
+        assert_eq!(args.budget_each, vec![]);
+        assert_eq!(args.budget_total, vec![]);
+        assert_eq!(args.by_extension, false);
+        assert_eq!(args.by_ignore, false);
+        assert_eq!(args.by_type, false);
         assert_eq!(args.color, false);
+        assert_eq!(args.columns, vec![Column::Bytes, Column::Path]);
+        assert_eq!(args.count_links, None);
         assert_eq!(args.depth, None);
+        assert_eq!(args.diff, None);
+        assert_eq!(args.dirs, false);
+        assert_eq!(args.disk_usage, false);
+        assert_eq!(args.duplicates, false);
+        assert_eq!(args.exclude, Vec::<String>::new());
+        assert_eq!(args.exclude_from, Vec::<PathBuf>::new());
+        assert_eq!(args.extension_parts, 1);
+        assert_eq!(args.files_from, None);
+        assert_eq!(args.fold_case, false);
         assert_eq!(args.follow, false);
+        assert_eq!(args.from0, false);
         assert_eq!(args.glob, None);
         assert_eq!(args.hidden, false);
         assert_eq!(args.highlow, false);
+        assert_eq!(args.ignore_file, Vec::<PathBuf>::new());
         assert_eq!(args.list_types, false);
+        assert_eq!(args.max_size, None);
         assert_eq!(args.metric_units, false);
+        assert_eq!(args.min_size, None);
+        assert_eq!(args.mtime, false);
         assert_eq!(args.name, false);
+        assert_eq!(args.newer_than, None);
+        assert_eq!(args.no_config, false);
+        assert_eq!(args.no_ignore, false);
+        assert_eq!(args.no_ignore_vcs, false);
+        assert_eq!(args.older_than, None);
+        assert_eq!(args.one_file_system, false);
+        assert_eq!(args.only_ignored, false);
+        assert_eq!(args.output, OutputFormat::Text);
         assert_eq!(args.parallel, false);
+        assert_eq!(args.paths_only, false);
+        assert_eq!(args.print0, false);
+        assert_eq!(args.save_snapshot, None);
+        assert_eq!(args.summary, false);
+        assert_eq!(args.top, None);
         assert_eq!(args.default_type, None);
+        assert_eq!(args.type_add, vec![]);
+        assert_eq!(args.type_not, None);
     }
 
     #[test]
@@ -185,18 +718,136 @@ mod tests {
         // the crate, and documentation of how to do it.
         let args = Args::parse_from(vec!["siz", "--color", "--binary-units", "foo"]);
 
-        assert_eq!(args.path, Some(PathBuf::from("foo")));
+        assert_eq!(args.paths, vec![PathBuf::from("foo")]);
         assert_eq!(args.binary_units, true);
+        assert_eq!(args.both_sizes, false);
+        assert_eq!(args.bottom, None);
+        assert_eq!(args.budget, None);
+        assert_eq!(args.budget_each, vec![]);
+        assert_eq!(args.budget_total, vec![]);
+        assert_eq!(args.by_extension, false);
+        assert_eq!(args.by_ignore, false);
+        assert_eq!(args.by_type, false);
         assert_eq!(args.color, true);
+        assert_eq!(args.columns, vec![Column::Bytes, Column::Path]);
+        assert_eq!(args.count_links, None);
         assert_eq!(args.depth, None);
+        assert_eq!(args.diff, None);
+        assert_eq!(args.dirs, false);
+        assert_eq!(args.disk_usage, false);
+        assert_eq!(args.duplicates, false);
+        assert_eq!(args.exclude, Vec::<String>::new());
+        assert_eq!(args.exclude_from, Vec::<PathBuf>::new());
+        assert_eq!(args.extension_parts, 1);
+        assert_eq!(args.files_from, None);
+        assert_eq!(args.fold_case, false);
         assert_eq!(args.follow, false);
+        assert_eq!(args.from0, false);
         assert_eq!(args.glob, None);
         assert_eq!(args.hidden, false);
         assert_eq!(args.highlow, false);
+        assert_eq!(args.ignore_file, Vec::<PathBuf>::new());
         assert_eq!(args.list_types, false);
+        assert_eq!(args.max_size, None);
         assert_eq!(args.metric_units, false);
+        assert_eq!(args.min_size, None);
+        assert_eq!(args.mtime, false);
         assert_eq!(args.name, false);
+        assert_eq!(args.newer_than, None);
+        assert_eq!(args.no_config, false);
+        assert_eq!(args.no_ignore, false);
+        assert_eq!(args.no_ignore_vcs, false);
+        assert_eq!(args.older_than, None);
+        assert_eq!(args.one_file_system, false);
+        assert_eq!(args.only_ignored, false);
+        assert_eq!(args.output, OutputFormat::Text);
         assert_eq!(args.parallel, false);
+        assert_eq!(args.paths_only, false);
+        assert_eq!(args.print0, false);
+        assert_eq!(args.save_snapshot, None);
+        assert_eq!(args.summary, false);
+        assert_eq!(args.top, None);
         assert_eq!(args.default_type, None);
+        assert_eq!(args.type_add, vec![]);
+        assert_eq!(args.type_not, None);
+    }
+
+    #[test]
+    fn test_args_parse_multiple_paths() {
+        let args = Args::parse_from(vec!["siz", "-l", "src", "Cargo.toml", "benches"]);
+
+        assert_eq!(
+            args.paths,
+            vec![
+                PathBuf::from("src"),
+                PathBuf::from("Cargo.toml"),
+                PathBuf::from("benches")
+            ]
+        );
+        assert_eq!(args.highlow, true);
+    }
+
+    #[test]
+    fn test_args_parse_files_from() {
+        let args = Args::parse_from(vec!["siz", "--files-from", "-", "--from0", "-l"]);
+
+        assert_eq!(args.paths, Vec::<PathBuf>::new());
+        assert_eq!(args.files_from, Some(PathBuf::from("-")));
+        assert_eq!(args.from0, true);
+        assert_eq!(args.highlow, true);
+
+        // the file list replaces the directory walk of the path arguments
+        assert!(Args::try_parse_from(vec!["siz", "--files-from", "list.txt", "."]).is_err());
+        assert!(Args::try_parse_from(vec!["siz", "--from0", "."]).is_err());
+    }
+
+    #[test]
+    fn test_args_parse_budget_conflicts() {
+        let args = Args::parse_from(vec!["siz", "--budget-each", "*.js=1KB", "."]);
+        assert_eq!(args.budget_each.len(), 1);
+
+        // the budget report does not write a report summary
+        for option in ["--budget", "--budget-each", "--budget-total"] {
+            assert!(
+                Args::try_parse_from(vec!["siz", option, "*.js=1KB", "--summary", "."]).is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_args_parse_print0() {
+        let args = Args::parse_from(vec!["siz", "-0", "--paths-only", "--top", "5", "."]);
+
+        assert_eq!(args.print0, true);
+        assert_eq!(args.paths_only, true);
+        assert_eq!(args.top, Some(5));
+
+        // ANSI color codes and summaries are not written in NUL-terminated output
+        assert!(Args::try_parse_from(vec!["siz", "-0", "--color", "."]).is_err());
+        assert!(Args::try_parse_from(vec!["siz", "-0", "--summary", "."]).is_err());
+    }
+
+    #[test]
+    fn test_args_parse_diff() {
+        let args = Args::parse_from(vec!["siz", "--diff", "old.json", "new", "-l", "-o", "json"]);
+
+        assert_eq!(args.paths, Vec::<PathBuf>::new());
+        assert_eq!(
+            args.diff,
+            Some(vec![PathBuf::from("old.json"), PathBuf::from("new")])
+        );
+        assert_eq!(args.highlow, true);
+        assert_eq!(args.output, OutputFormat::Json);
+
+        // a directory named diff is a path argument
+        let args = Args::parse_from(vec!["siz", "diff"]);
+        assert_eq!(args.paths, vec![PathBuf::from("diff")]);
+        assert_eq!(args.diff, None);
+
+        assert!(Args::try_parse_from(vec!["siz", "--diff", "old.json"]).is_err());
+        assert!(Args::try_parse_from(vec!["siz", "--diff", "old.json", "new", "."]).is_err());
+        assert!(
+            Args::try_parse_from(vec!["siz", "--diff", "old.json", "new", "--summary"]).is_err()
+        );
     }
 }
@@ -0,0 +1,423 @@
+//! This module provides support for size budget assertions.
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use ignore::overrides::{Override, OverrideBuilder};
+
+use crate::args::Args;
+use crate::filter::parse_size;
+use crate::record::FileRecord;
+
+/// The process exit status code when a size budget is exceeded.
+///
+/// The exit status code is distinct from the general error exit status
+/// code (1) and the command line usage error exit status code (2).
+pub const BUDGET_EXIT_CODE: u8 = 3;
+
+/// `BudgetKind` is an enum that represents the kinds of size budget rules.
+///
+/// # Variants
+///
+/// * `Total`: The total size of the matching files must not exceed the limit.
+/// * `Each`: The size of each matching file must not exceed the limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BudgetKind {
+    Total,
+    Each,
+}
+
+impl BudgetKind {
+    /// Returns the report name of the budget kind.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BudgetKind::Total => "total",
+            BudgetKind::Each => "each",
+        }
+    }
+}
+
+/// `BudgetLimit` is a struct that represents a `PATTERN=SIZE` size limit.
+///
+/// # Fields
+///
+/// * `pattern`: The glob pattern of the files that the limit applies to.
+/// * `limit`: The size limit in bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BudgetLimit {
+    pub pattern: String,
+    pub limit: u64,
+}
+
+/// Parses a `PATTERN=SIZE` size limit string into a `BudgetLimit`.
+///
+/// The size is split from the pattern at the last `=` character and supports the
+/// SI metric and binary units of the size filters.
+///
+/// # Examples
+///
+/// ```
+/// use siz::budget::parse_budget_limit;
+///
+/// let budget_limit = parse_budget_limit("dist/**/*.js=500KiB").unwrap();
+/// assert_eq!(budget_limit.pattern, "dist/**/*.js");
+/// assert_eq!(budget_limit.limit, 500 * 1024);
+/// assert!(parse_budget_limit("500KiB").is_err());
+/// ```
+pub fn parse_budget_limit(budget_limit: &str) -> Result<BudgetLimit, String> {
+    match budget_limit.rsplit_once('=') {
+        Some((pattern, size)) if !pattern.trim().is_empty() => Ok(BudgetLimit {
+            pattern: pattern.trim().to_string(),
+            limit: parse_size(size)?,
+        }),
+        _ => Err(format!(
+            "invalid budget '{}': expected PATTERN=SIZE, e.g., '**/*.js=500KiB'",
+            budget_limit
+        )),
+    }
+}
+
+/// `BudgetRule` is a struct that represents a size budget rule.
+///
+/// # Fields
+///
+/// * `kind`: The `BudgetKind` of the rule.
+/// * `pattern`: The glob pattern of the files that the rule applies to.
+/// * `limit`: The size limit in bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BudgetRule {
+    pub kind: BudgetKind,
+    pub pattern: String,
+    pub limit: u64,
+}
+
+impl BudgetRule {
+    /// Parses a budget file rule line with the format `total|each PATTERN SIZE`.
+    fn parse_line(line: &str) -> Result<Self, String> {
+        let (kind, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let kind = match kind {
+            "total" => BudgetKind::Total,
+            "each" => BudgetKind::Each,
+            _ => {
+                return Err(format!(
+                    "unknown budget kind '{}': expected 'total' or 'each'",
+                    kind
+                ))
+            }
+        };
+        match rest.trim().rsplit_once(char::is_whitespace) {
+            Some((pattern, size)) => Ok(Self {
+                kind,
+                pattern: pattern.trim().to_string(),
+                limit: parse_size(size)?,
+            }),
+            None => Err("expected a rule with the format 'total|each PATTERN SIZE'".to_string()),
+        }
+    }
+}
+
+/// `BudgetViolation` is a struct that represents a size budget rule that was exceeded.
+///
+/// # Fields
+///
+/// * `rule`: The `BudgetRule` that was exceeded.
+/// * `size`: The total size of the matching files for a `Total` rule, or the file size for
+///   an `Each` rule, in bytes.
+/// * `path`: The path of the file that exceeded an `Each` rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BudgetViolation {
+    pub rule: BudgetRule,
+    pub size: u64,
+    pub path: Option<PathBuf>,
+}
+
+/// `Budget` is a struct that evaluates size budget rules against the files of a walk.
+///
+/// The rule glob patterns use gitignore-style matching, relative to the root path of the
+/// walk that contains a file: patterns without a `/` match the file name in any directory.
+/// The rules apply to the files of all root paths.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+///
+/// use siz::budget::{Budget, BudgetKind, BudgetRule};
+/// use siz::record::FileRecord;
+///
+/// let rules = vec![BudgetRule {
+///     kind: BudgetKind::Total,
+///     pattern: "*.js".to_string(),
+///     limit: 100,
+/// }];
+/// let mut budget = Budget::new(&[PathBuf::from("dist")], rules).unwrap();
+/// budget.add(&FileRecord::from_size(60, PathBuf::from("dist/a.js")));
+/// budget.add(&FileRecord::from_size(60, PathBuf::from("dist/b.js")));
+/// budget.add(&FileRecord::from_size(60, PathBuf::from("dist/c.css")));
+///
+/// let violations = budget.into_violations();
+/// assert_eq!(violations.len(), 1);
+/// assert_eq!(violations[0].size, 120);
+/// ```
+#[derive(Debug)]
+pub struct Budget {
+    roots: Vec<PathBuf>,
+    rules: Vec<(BudgetRule, Vec<Override>)>,
+    totals: Vec<u64>,
+    violations: Vec<(usize, BudgetViolation)>,
+}
+
+impl Budget {
+    /// Creates a new `Budget` with the `rules` for a walk of the `roots` paths.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a rule glob pattern is invalid.
+    pub fn new(roots: &[PathBuf], rules: Vec<BudgetRule>) -> Result<Self> {
+        let rules = rules
+            .into_iter()
+            .map(|rule| {
+                let ovrs = roots
+                    .iter()
+                    .map(|root| {
+                        let mut ovrb = OverrideBuilder::new(root);
+                        ovrb.add(&rule.pattern)?;
+                        Ok(ovrb.build()?)
+                    })
+                    .collect::<Result<Vec<Override>>>()?;
+                Ok((rule, ovrs))
+            })
+            .collect::<Result<Vec<(BudgetRule, Vec<Override>)>>>()?;
+        Ok(Self {
+            roots: roots.to_vec(),
+            totals: vec![0; rules.len()],
+            rules,
+            violations: Vec::new(),
+        })
+    }
+
+    /// Creates a new `Budget` from the budget options that are defined on the command line,
+    /// or returns `None` when no budget rules are defined.
+    ///
+    /// The rules of the budget file are evaluated before the command line rules.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the budget file cannot be read or parsed, or a rule glob pattern
+    /// is invalid.
+    pub fn from_args(args: &Args) -> Result<Option<Self>> {
+        let mut rules: Vec<BudgetRule> = match &args.budget {
+            Some(budget_path) => read_budget_file(budget_path)?,
+            None => Vec::new(),
+        };
+        for (kind, budget_limits) in [
+            (BudgetKind::Total, &args.budget_total),
+            (BudgetKind::Each, &args.budget_each),
+        ] {
+            rules.extend(budget_limits.iter().map(|budget_limit| BudgetRule {
+                kind,
+                pattern: budget_limit.pattern.clone(),
+                limit: budget_limit.limit,
+            }));
+        }
+        if rules.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self::new(&args.paths, rules)?))
+    }
+
+    /// Returns the number of budget rules.
+    pub fn rule_count(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Evaluates the budget rules against a report file.
+    pub fn add(&mut self, record: &FileRecord) {
+        // the rules match relative to the first root path that contains the file
+        let root_index = self
+            .roots
+            .iter()
+            .position(|root| record.path.starts_with(root))
+            .unwrap_or(0);
+        for (i, (rule, ovrs)) in self.rules.iter().enumerate() {
+            if !ovrs[root_index].matched(&record.path, false).is_whitelist() {
+                continue;
+            }
+            match rule.kind {
+                BudgetKind::Total => self.totals[i] += record.size,
+                BudgetKind::Each => {
+                    if record.size > rule.limit {
+                        self.violations.push((
+                            i,
+                            BudgetViolation {
+                                rule: rule.clone(),
+                                size: record.size,
+                                path: Some(record.path.clone()),
+                            },
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the budget rule violations, in rule order.  The violations of an `Each`
+    /// rule are sorted by descending file size.
+    pub fn into_violations(self) -> Vec<BudgetViolation> {
+        let mut violations = self.violations;
+        for (i, ((rule, _), total)) in self.rules.into_iter().zip(self.totals).enumerate() {
+            if rule.kind == BudgetKind::Total && total > rule.limit {
+                violations.push((
+                    i,
+                    BudgetViolation {
+                        rule,
+                        size: total,
+                        path: None,
+                    },
+                ));
+            }
+        }
+        violations.sort_by(|(a_index, a), (b_index, b)| {
+            (a_index, b.size, &a.path).cmp(&(b_index, a.size, &b.path))
+        });
+        violations
+            .into_iter()
+            .map(|(_, violation)| violation)
+            .collect()
+    }
+}
+
+/// Reads the budget rules of a budget file.
+///
+/// Budget files define one `total|each PATTERN SIZE` rule per line.  Blank lines and
+/// lines that start with `#` are ignored.
+fn read_budget_file(path: &Path) -> Result<Vec<BudgetRule>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("unable to read budget file: {}", path.display()))?;
+    let mut rules = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match BudgetRule::parse_line(line) {
+            Ok(rule) => rules.push(rule),
+            Err(err) => anyhow::bail!(
+                "invalid budget rule on line {} of {}: {}",
+                i + 1,
+                path.display(),
+                err
+            ),
+        }
+    }
+    Ok(rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn rule(kind: BudgetKind, pattern: &str, limit: u64) -> BudgetRule {
+        BudgetRule {
+            kind,
+            pattern: pattern.to_string(),
+            limit,
+        }
+    }
+
+    #[test]
+    fn test_parse_budget_limit() {
+        assert_eq!(
+            parse_budget_limit("a=b=1kB"),
+            Ok(BudgetLimit {
+                pattern: "a=b".to_string(),
+                limit: 1000
+            })
+        );
+        assert!(parse_budget_limit("=1kB").is_err());
+        assert!(parse_budget_limit("*.js=lots").is_err());
+    }
+
+    #[test]
+    fn test_budget_rule_parse_line() {
+        assert_eq!(
+            BudgetRule::parse_line("total dist/**/*.js 500KiB"),
+            Ok(rule(BudgetKind::Total, "dist/**/*.js", 500 * 1024))
+        );
+        assert_eq!(
+            BudgetRule::parse_line("each  **  10MB"),
+            Ok(rule(BudgetKind::Each, "**", 10_000_000))
+        );
+        assert!(BudgetRule::parse_line("most ** 10MB").is_err());
+        assert!(BudgetRule::parse_line("each 10MB").is_err());
+    }
+
+    #[test]
+    fn test_budget_violations() -> Result<()> {
+        let rules = vec![
+            rule(BudgetKind::Total, "dist/**/*.js", 100),
+            rule(BudgetKind::Each, "*.png", 10),
+            rule(BudgetKind::Total, "*.css", 100),
+        ];
+        let mut budget = Budget::new(&[PathBuf::from("root")], rules)?;
+        for (size, path) in [
+            (60, "root/dist/a.js"),
+            (60, "root/dist/sub/b.js"),
+            (60, "root/src/c.js"),
+            (11, "root/img/small.png"),
+            (50, "root/img/large.png"),
+            (5, "root/img/tiny.png"),
+            (99, "root/style.css"),
+        ] {
+            budget.add(&FileRecord::from_size(size, PathBuf::from(path)));
+        }
+        let violations: Vec<(BudgetKind, u64, Option<PathBuf>)> = budget
+            .into_violations()
+            .into_iter()
+            .map(|violation| (violation.rule.kind, violation.size, violation.path))
+            .collect();
+        assert_eq!(
+            violations,
+            vec![
+                (BudgetKind::Total, 120, None),
+                (
+                    BudgetKind::Each,
+                    50,
+                    Some(PathBuf::from("root/img/large.png"))
+                ),
+                (
+                    BudgetKind::Each,
+                    11,
+                    Some(PathBuf::from("root/img/small.png"))
+                ),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_budget_file() -> Result<()> {
+        let td = TempDir::new()?;
+        let budget_path = td.path().join("budget.txt");
+        std::fs::write(
+            &budget_path,
+            "# bundle budgets\n\ntotal dist/**/*.js 500KiB\neach ** 10MB\n",
+        )?;
+        let invalid_path = td.path().join("invalid.txt");
+        std::fs::write(&invalid_path, "total *.js\n")?;
+
+        assert_eq!(
+            read_budget_file(&budget_path)?,
+            vec![
+                rule(BudgetKind::Total, "dist/**/*.js", 500 * 1024),
+                rule(BudgetKind::Each, "**", 10_000_000),
+            ]
+        );
+        assert!(read_budget_file(&invalid_path)
+            .unwrap_err()
+            .to_string()
+            .contains("line 1"));
+        Ok(())
+    }
+}
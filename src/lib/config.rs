@@ -0,0 +1,685 @@
+//! This module provides support for siz.toml configuration files.
+use std::any::TypeId;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, ArgMatches, CommandFactory, FromArgMatches};
+
+use crate::args::Args;
+use crate::filelist::STDIN_PATH;
+
+/// The configuration file name.
+pub const CONFIG_FILE_NAME: &str = "siz.toml";
+
+/// The argument ids that cannot be defined in a configuration file.
+const EXCLUDED_IDS: [&str; 5] = ["help", "version", "no_config", "list_types", "diff"];
+
+/// The argument id prefix of the boolean option negation flags.
+const NEGATION_ID_PREFIX: &str = "negate_";
+
+/// The command line tokens and path arguments of a configuration file option.
+type OptionTokens = (Vec<OsString>, Vec<OsString>);
+
+/// `Config` is a struct that represents the default option values of the siz.toml
+/// configuration files.
+///
+/// Configuration files define option defaults with the `Args` field names or the long
+/// option names as keys, e.g.:
+///
+/// ```toml
+/// color = true
+/// binary-units = true
+/// hidden = true
+/// glob = ["*.rs", "*.toml"]
+/// depth = 3
+/// ```
+///
+/// Boolean options are enabled with `true`, options that take a value are defined with
+/// a string or number, and repeatable options are defined with an array.  A `false`
+/// boolean value leaves the option at its default value.  The command line disables a
+/// boolean option that a configuration file enables with its negation flag, e.g.,
+/// `--no-color`.
+///
+/// User-defined file type aliases are defined in a `types` table with the glob patterns
+/// of each type alias, and are added to the type aliases of the `--type-add` option.  The
+/// `types` tables of the configuration files are merged by type alias name:
+///
+/// ```toml
+/// [types]
+/// ml-weights = ["*.safetensors", "*.ckpt", "*.pt"]
+/// ```
+#[derive(Debug, Default)]
+pub struct Config {
+    files: Vec<(PathBuf, toml::Table)>,
+}
+
+impl Config {
+    /// Loads the configuration files at `paths`.  The option values of a configuration
+    /// file override the option values of the configuration files that precede it,
+    /// including the values of the options that conflict with its options.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a configuration file cannot be read or is not valid TOML.
+    pub fn load(paths: &[PathBuf]) -> Result<Self> {
+        let mut config = Self::default();
+        for path in paths {
+            let contents = std::fs::read_to_string(path).with_context(|| {
+                format!("unable to read configuration file: {}", path.display())
+            })?;
+            let table: toml::Table = contents
+                .parse()
+                .with_context(|| format!("invalid configuration file: {}", path.display()))?;
+            config.files.push((path.clone(), table));
+        }
+        Ok(config)
+    }
+
+    /// Returns the command line tokens of the configuration file options that apply to
+    /// the command line `argv`, and the configuration file path arguments when the
+    /// command line does not define a path or the diff option.  The path arguments are
+    /// defined with a `path` string or a `paths` array.
+    ///
+    /// The configuration file options are applied as defaults of the parsed command line
+    /// `matches`.  An option is skipped when:
+    ///
+    /// * the command line defines it or an option that conflicts with it.
+    /// * a configuration file with a higher precedence defines it or an option that
+    ///   conflicts with it.
+    /// * the options that it requires are not defined.
+    fn arg_tokens(&self, argv: &[OsString], matches: &ArgMatches) -> Result<OptionTokens> {
+        let mut cmd = command();
+        cmd.build();
+        let cli_args: Vec<&Arg> = cmd
+            .get_arguments()
+            .filter(|arg| {
+                matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+            })
+            .collect();
+        let conflicts = |a: &Arg, b: &Arg| {
+            a.get_id() == b.get_id()
+                || cmd
+                    .get_arg_conflicts_with(a)
+                    .iter()
+                    .any(|conflict| conflict.get_id() == b.get_id())
+                || cmd
+                    .get_arg_conflicts_with(b)
+                    .iter()
+                    .any(|conflict| conflict.get_id() == a.get_id())
+        };
+        let mut defined: Vec<(&Arg, usize)> = Vec::new();
+        let mut type_tokens: BTreeMap<&str, String> = BTreeMap::new();
+        let mut options: Vec<OptionTokens> = Vec::new();
+        // the configuration files are visited in descending precedence order
+        for (level, (source, table)) in self.files.iter().enumerate().rev() {
+            for (key, value) in table {
+                let invalid_value = || {
+                    anyhow::anyhow!(
+                        "invalid value for option '{}' in configuration file {}",
+                        key,
+                        source.display()
+                    )
+                };
+                // the user-defined file type aliases of the types tables are added to
+                // the user-defined file type aliases of the command line, and a type
+                // alias replaces the type alias of a lower precedence file
+                if key == "types" {
+                    let types = value.as_table().ok_or_else(invalid_value)?;
+                    for (name, globs) in types {
+                        let globs: Vec<&str> = match globs {
+                            toml::Value::Array(items) => items
+                                .iter()
+                                .map(|glob| glob.as_str().ok_or_else(invalid_value))
+                                .collect::<Result<_>>()?,
+                            glob => vec![glob.as_str().ok_or_else(invalid_value)?],
+                        };
+                        type_tokens
+                            .entry(name.as_str())
+                            .or_insert_with(|| format!("--type-add={}:{}", name, globs.join(",")));
+                    }
+                    continue;
+                }
+                // the path key is the singular form of the positional paths argument
+                let id = match key.as_str() {
+                    "path" => "paths".to_string(),
+                    key => key.replace('-', "_"),
+                };
+                let arg = cmd
+                    .get_arguments()
+                    .find(|arg| arg.get_id() == id.as_str() || arg.get_long() == Some(key.as_str()))
+                    .filter(|arg| {
+                        !EXCLUDED_IDS.contains(&arg.get_id().as_str())
+                            && !arg.get_id().as_str().starts_with(NEGATION_ID_PREFIX)
+                    })
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "unknown option '{}' in configuration file {}",
+                            key,
+                            source.display()
+                        )
+                    })?;
+                let config_dir = source.parent().unwrap_or(Path::new(""));
+                let tokens = option_tokens(arg, value, config_dir).ok_or_else(invalid_value)?;
+                // the command line takes precedence over the configuration files, and
+                // a configuration file takes precedence over the files that precede it
+                if cli_args.iter().any(|cli_arg| conflicts(cli_arg, arg))
+                    || defined.iter().any(|(defined_arg, defined_level)| {
+                        *defined_level > level && conflicts(defined_arg, arg)
+                    })
+                    || defined
+                        .iter()
+                        .any(|(defined_arg, _)| defined_arg.get_id() == arg.get_id())
+                {
+                    continue;
+                }
+                defined.push((arg, level));
+                options.push(tokens);
+            }
+        }
+        if !type_tokens.is_empty() {
+            options.push((
+                type_tokens.into_values().map(OsString::from).collect(),
+                Vec::new(),
+            ));
+        }
+
+        // the options whose required options are not defined on the command line or in
+        // the configuration files are skipped
+        let mut applied: OptionTokens = (Vec::new(), Vec::new());
+        loop {
+            let (met, unmet): (Vec<_>, Vec<_>) =
+                options.into_iter().partition(|(tokens, paths)| {
+                    let probe_argv = config_argv(
+                        argv,
+                        &[applied.0.as_slice(), tokens].concat(),
+                        &[applied.1.as_slice(), paths].concat(),
+                    );
+                    !matches!(
+                        cmd.clone().try_get_matches_from(probe_argv),
+                        Err(err) if err.kind() == ErrorKind::MissingRequiredArgument
+                    )
+                });
+            if met.is_empty() {
+                break;
+            }
+            for (tokens, paths) in met {
+                applied.0.extend(tokens);
+                applied.1.extend(paths);
+            }
+            options = unmet;
+        }
+        Ok(applied)
+    }
+}
+
+/// Returns the command line tokens and path arguments of a configuration file option
+/// `value` of the argument `arg`, or `None` if the value is not valid for the argument.
+///
+/// Relative path values are resolved against the directory `config_dir` of the
+/// configuration file.
+fn option_tokens(arg: &Arg, value: &toml::Value, config_dir: &Path) -> Option<OptionTokens> {
+    let values = match value {
+        toml::Value::Array(items) => items.iter().collect(),
+        value => vec![value],
+    };
+    let is_path = arg.get_value_parser().type_id() == TypeId::of::<PathBuf>();
+    let Some(long) = arg.get_long() else {
+        // the positional path arguments
+        let paths = values
+            .into_iter()
+            .map(|value| value.as_str().map(|path| resolve_path(config_dir, path)))
+            .collect::<Option<_>>()?;
+        return Some((Vec::new(), paths));
+    };
+    let mut tokens = Vec::with_capacity(values.len());
+    for value in values {
+        let takes_values = arg.get_action().takes_values();
+        let value = match value {
+            toml::Value::Boolean(enabled) if !takes_values => {
+                if *enabled {
+                    tokens.push(format!("--{}", long).into());
+                }
+                continue;
+            }
+            // the standard input stream path of the files-from option is not a file path
+            toml::Value::String(value) if is_path && value != STDIN_PATH => {
+                let mut token = OsString::from(format!("--{}=", long));
+                token.push(resolve_path(config_dir, value));
+                tokens.push(token);
+                continue;
+            }
+            toml::Value::String(value) if takes_values => value.clone(),
+            toml::Value::Integer(value) if takes_values => value.to_string(),
+            toml::Value::Float(value) if takes_values => value.to_string(),
+            toml::Value::Datetime(value) if takes_values => value.to_string(),
+            _ => return None,
+        };
+        tokens.push(format!("--{}={}", long, value).into());
+    }
+    Some((tokens, Vec::new()))
+}
+
+/// Returns the configuration file path value `path` resolved against the directory
+/// `config_dir` of the configuration file.  Paths below the current working directory
+/// are returned relative to it so that the report paths match command line paths.
+fn resolve_path(config_dir: &Path, path: &str) -> OsString {
+    let path = config_dir.join(path);
+    let relative = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok().map(Path::to_path_buf));
+    match relative {
+        Some(relative) if relative.as_os_str().is_empty() => OsString::from("."),
+        Some(relative) => relative.into_os_string(),
+        None => path.into_os_string(),
+    }
+}
+
+/// Returns the command line `argv` with the configuration file option `tokens` before
+/// the command line options and the configuration file `paths` after them.
+fn config_argv(argv: &[OsString], tokens: &[OsString], paths: &[OsString]) -> Vec<OsString> {
+    let mut config_argv: Vec<OsString> =
+        Vec::with_capacity(argv.len() + tokens.len() + paths.len());
+    config_argv.extend(argv.first().cloned());
+    config_argv.extend(tokens.iter().cloned());
+    config_argv.extend(argv.iter().skip(1).cloned());
+    config_argv.extend(paths.iter().cloned());
+    config_argv
+}
+
+/// Returns the siz command with a negation flag for each boolean option, so that the
+/// command line can disable the boolean options that a configuration file enables.
+///
+/// The negation flag of an option is `--no-<option>`, or the option name without the
+/// `no-` prefix for the options that disable a default behavior (e.g., `--ignore` for
+/// `--no-ignore`).  The last of an option and its negation flag takes precedence.
+fn command() -> clap::Command {
+    let mut cmd = Args::command();
+    let flags: Vec<(String, String)> = cmd
+        .get_arguments()
+        .filter(|arg| matches!(arg.get_action(), ArgAction::SetTrue))
+        .filter(|arg| !EXCLUDED_IDS.contains(&arg.get_id().as_str()))
+        .filter_map(|arg| Some((arg.get_id().to_string(), arg.get_long()?.to_string())))
+        .collect();
+    for (id, long) in flags {
+        let negation_id = format!("{}{}", NEGATION_ID_PREFIX, id);
+        let negation_long = match long.strip_prefix("no-") {
+            Some(long) => long.to_string(),
+            None => format!("no-{}", long),
+        };
+        cmd = cmd
+            .mut_arg(&id, |arg| arg.overrides_with(negation_id.clone()))
+            .arg(
+                Arg::new(negation_id)
+                    .long(negation_long)
+                    .action(ArgAction::SetTrue)
+                    .overrides_with(id)
+                    .hide(true),
+            );
+    }
+    cmd
+}
+
+/// Returns the paths of the configuration files that exist, in ascending precedence order:
+/// the user-level configuration file, then the project-local configuration file.
+///
+/// The user-level configuration file is `$XDG_CONFIG_HOME/siz/siz.toml` (default:
+/// `~/.config/siz/siz.toml`), or `%APPDATA%\siz\siz.toml` on Windows.  The project-local
+/// configuration file is the siz.toml file in the current working directory or in the
+/// nearest parent directory.
+pub fn config_paths() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = Vec::with_capacity(2);
+    paths.extend(user_config_path().filter(|path| path.is_file()));
+    if let Ok(cwd) = std::env::current_dir() {
+        paths.extend(project_config_path(&cwd).filter(|path| !paths.contains(path)));
+    }
+    paths
+}
+
+/// Returns the user-level configuration file path.
+fn user_config_path() -> Option<PathBuf> {
+    let config_dir = match std::env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None if cfg!(windows) => PathBuf::from(std::env::var_os("APPDATA")?),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_dir.join("siz").join(CONFIG_FILE_NAME))
+}
+
+/// Returns the path of the siz.toml file in the `start` directory or in the nearest
+/// parent directory.
+fn project_config_path(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+/// Parses the command line arguments of the process with the option defaults of the
+/// user-level and project-local configuration files.
+///
+/// The command line options take precedence over the configuration files, and the
+/// configuration files are not loaded when the `--no-config` option is used.  Command
+/// line parsing errors exit the process with the clap error message.
+pub fn parse_args() -> Result<Args> {
+    parse_args_from(std::env::args_os(), &config_paths())
+}
+
+/// Parses the command line arguments in `itr` with the option defaults of the
+/// configuration files at `config_paths`.
+///
+/// # Examples
+///
+/// ```
+/// use siz::config::parse_args_from;
+///
+/// let args = parse_args_from(vec!["siz", "--binary-units", "."], &[]).unwrap();
+/// assert!(args.binary_units);
+/// ```
+pub fn parse_args_from<I, T>(itr: I, config_paths: &[PathBuf]) -> Result<Args>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let argv: Vec<OsString> = itr.into_iter().map(Into::into).collect();
+    let matches = command()
+        .try_get_matches_from(&argv)
+        .unwrap_or_else(|err| err.exit());
+    if config_paths.is_empty() || matches.get_flag("no_config") {
+        return Ok(Args::from_arg_matches(&matches)?);
+    }
+    let (tokens, paths) = Config::load(config_paths)?.arg_tokens(&argv, &matches)?;
+    if tokens.is_empty() && paths.is_empty() {
+        return Ok(Args::from_arg_matches(&matches)?);
+    }
+    // the configuration file options precede the command line options
+    let matches = command()
+        .try_get_matches_from(config_argv(&argv, &tokens, &paths))
+        .unwrap_or_else(|err| err.exit());
+    Ok(Args::from_arg_matches(&matches)?)
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use crate::args::OutputFormat;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn write_config(td: &TempDir, name: &str, contents: &str) -> PathBuf {
+        let path = td.path().join(name);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_parse_args_from_config() -> Result<()> {
+        let td = TempDir::new()?;
+        let user = write_config(
+            &td,
+            "user.toml",
+            "binary-units = true\nhidden = true\ndepth = 2\nglob = [\"*.rs\", \"*.toml\"]\n",
+        );
+        let project = write_config(&td, "project.toml", "depth = 3\noutput = \"csv\"\n");
+        let args = parse_args_from(vec!["siz", "."], &[user, project])?;
+
+        assert_eq!(args.binary_units, true);
+        assert_eq!(args.hidden, true);
+        assert_eq!(args.depth, Some(3));
+        assert_eq!(
+            args.glob,
+            Some(vec!["*.rs".to_string(), "*.toml".to_string()])
+        );
+        assert_eq!(args.output, OutputFormat::Csv);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_args_from_command_line_precedence() -> Result<()> {
+        let td = TempDir::new()?;
+        let config = write_config(
+            &td,
+            "siz.toml",
+            "binary_units = true\nhighlow = true\ndepth = 2\npath = \"src\"\n",
+        );
+        let args = parse_args_from(
+            vec!["siz", "--metric-units", "--name", "--depth", "5", "."],
+            &[config],
+        )?;
+
+        // conflicting configuration file options are skipped
+        assert_eq!(args.binary_units, false);
+        assert_eq!(args.metric_units, true);
+        assert_eq!(args.highlow, false);
+        assert_eq!(args.name, true);
+        assert_eq!(args.depth, Some(5));
+        assert_eq!(args.paths, vec![PathBuf::from(".")]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_args_from_config_path_and_no_config() -> Result<()> {
+        let td = TempDir::new()?;
+        let config = write_config(&td, "siz.toml", "path = \"src\"\ncolor = true\n");
+        let args = parse_args_from(vec!["siz"], std::slice::from_ref(&config))?;
+        let no_config_args = parse_args_from(vec!["siz", "--no-config", "."], &[config])?;
+
+        assert_eq!(args.paths, vec![td.path().join("src")]);
+        assert_eq!(args.color, true);
+        assert_eq!(no_config_args.paths, vec![PathBuf::from(".")]);
+        assert_eq!(no_config_args.color, false);
+
+        let config = write_config(&td, "paths.toml", "paths = [\"src\", \"benches\"]\n");
+        let args = parse_args_from(vec!["siz"], &[config])?;
+        assert_eq!(
+            args.paths,
+            vec![td.path().join("src"), td.path().join("benches")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_args_from_config_relative_paths() -> Result<()> {
+        let td = TempDir::new()?;
+        let absolute = td.path().join("absolute.ignore");
+        let config = write_config(
+            &td,
+            "siz.toml",
+            &format!(
+                "exclude-from = \"excl.txt\"\nignore-file = [\"a.ignore\", {:?}]\nbudget = \"ci/budget.txt\"\n",
+                absolute.to_str().unwrap()
+            ),
+        );
+        let args = parse_args_from(vec!["siz", "."], std::slice::from_ref(&config))?;
+
+        // the relative paths are resolved against the configuration file directory
+        assert_eq!(args.exclude_from, vec![td.path().join("excl.txt")]);
+        assert_eq!(args.ignore_file, vec![td.path().join("a.ignore"), absolute]);
+        assert_eq!(args.budget, Some(td.path().join("ci/budget.txt")));
+
+        // the standard input stream file list is not a file path
+        let config = write_config(&td, "stdin.toml", "files-from = \"-\"\n");
+        let args = parse_args_from(vec!["siz"], &[config])?;
+        assert_eq!(args.files_from, Some(PathBuf::from("-")));
+
+        // paths below the current working directory remain relative to it
+        let cwd = std::env::current_dir()?;
+        assert_eq!(resolve_path(&cwd, "src"), OsString::from("src"));
+        assert_eq!(resolve_path(&cwd, "."), OsString::from("."));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_args_from_config_negation_flags() -> Result<()> {
+        let td = TempDir::new()?;
+        let config = write_config(
+            &td,
+            "siz.toml",
+            "color = true\nhidden = true\nfollow = true\nno-ignore = true\nsummary = true\n",
+        );
+        let args = parse_args_from(
+            vec!["siz", "--no-color", "--no-hidden", "--ignore", "."],
+            std::slice::from_ref(&config),
+        )?;
+
+        // the negation flags disable the configuration file boolean options
+        assert_eq!(args.color, false);
+        assert_eq!(args.hidden, false);
+        assert_eq!(args.no_ignore, false);
+        assert_eq!(args.follow, true);
+        assert_eq!(args.summary, true);
+
+        // the last of an option and its negation flag takes precedence
+        let args = parse_args_from(vec!["siz", "--no-follow", "--follow", "."], &[config])?;
+        assert_eq!(args.follow, true);
+        let args = parse_args_from(vec!["siz", "--summary", "--no-summary", "."], &[])?;
+        assert_eq!(args.summary, false);
+
+        // the negation flags are not configuration file options
+        let negation = write_config(&td, "negation.toml", "no-color = true\n");
+        assert!(parse_args_from(vec!["siz", "."], &[negation])
+            .unwrap_err()
+            .to_string()
+            .contains("unknown option 'no-color'"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_args_from_config_types() -> Result<()> {
+        let td = TempDir::new()?;
+        let config = write_config(
+            &td,
+            "siz.toml",
+            "[types]\nml-weights = [\"*.safetensors\", \"*.{ckpt,pt}\"]\nonnx = \"*.onnx\"\n",
+        );
+        let args = parse_args_from(vec!["siz", "--type-add", "lock:*.lock", "."], &[config])?;
+
+        let type_names: Vec<&str> = args.type_add.iter().map(|ty| ty.name.as_str()).collect();
+        assert_eq!(type_names, vec!["ml-weights", "onnx", "lock"]);
+        assert_eq!(args.type_add[0].globs, vec!["*.safetensors", "*.{ckpt,pt}"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_args_from_invalid_config() -> Result<()> {
+        let td = TempDir::new()?;
+        let unknown = write_config(&td, "unknown.toml", "colour = true\n");
+        let invalid_value = write_config(&td, "invalid_value.toml", "color = \"yes\"\n");
+        let no_config = write_config(&td, "no_config.toml", "no_config = true\n");
+        let list_types = write_config(&td, "list_types.toml", "list-types = true\n");
+        let invalid_toml = write_config(&td, "invalid_toml.toml", "color = \n");
+
+        assert!(parse_args_from(vec!["siz", "."], &[unknown])
+            .unwrap_err()
+            .to_string()
+            .contains("unknown option 'colour'"));
+        assert!(parse_args_from(vec!["siz", "."], &[invalid_value])
+            .unwrap_err()
+            .to_string()
+            .contains("invalid value for option 'color'"));
+        assert!(parse_args_from(vec!["siz", "."], &[no_config]).is_err());
+        assert!(parse_args_from(vec!["siz", "."], &[list_types])
+            .unwrap_err()
+            .to_string()
+            .contains("unknown option 'list-types'"));
+        assert!(parse_args_from(vec!["siz", "."], &[invalid_toml]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_args_from_config_unmet_requires() -> Result<()> {
+        let td = TempDir::new()?;
+        let config = write_config(
+            &td,
+            "siz.toml",
+            "fold-case = true\nextension-parts = 2\nfrom0 = true\ncolor = true\n",
+        );
+        let args = parse_args_from(vec!["siz", "."], std::slice::from_ref(&config))?;
+
+        // the options that require an undefined option are skipped
+        assert_eq!(args.fold_case, false);
+        assert_eq!(args.extension_parts, 1);
+        assert_eq!(args.from0, false);
+        assert_eq!(args.color, true);
+
+        let args = parse_args_from(vec!["siz", "--by-extension", "."], &[config])?;
+        assert_eq!(args.fold_case, true);
+        assert_eq!(args.extension_parts, 2);
+        assert_eq!(args.from0, false);
+
+        // the required options can be defined in the configuration files
+        let user = write_config(&td, "user.toml", "fold-case = true\n");
+        let project = write_config(&td, "project.toml", "by-extension = true\n");
+        let args = parse_args_from(vec!["siz", "."], &[user, project])?;
+        assert_eq!(args.by_extension, true);
+        assert_eq!(args.fold_case, true);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_args_from_config_diff() -> Result<()> {
+        let td = TempDir::new()?;
+        let config = write_config(
+            &td,
+            "siz.toml",
+            "summary = true\ndirs = true\ntop = 5\npath = \"src\"\nbinary-units = true\n",
+        );
+        let args = parse_args_from(vec!["siz", "--diff", "old", "new"], &[config])?;
+
+        // the options that the diff option does not support are skipped
+        assert_eq!(args.summary, false);
+        assert_eq!(args.dirs, false);
+        assert_eq!(args.top, None);
+        assert_eq!(args.paths, Vec::<PathBuf>::new());
+        assert_eq!(args.binary_units, true);
+        assert_eq!(
+            args.diff,
+            Some(vec![PathBuf::from("old"), PathBuf::from("new")])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_args_from_config_file_precedence() -> Result<()> {
+        let td = TempDir::new()?;
+        let user = write_config(
+            &td,
+            "user.toml",
+            "binary-units = true\nhighlow = true\ndepth = 2\n[types]\nweights = \"*.pt\"\nlock = \"*.old\"\n",
+        );
+        let project = write_config(
+            &td,
+            "project.toml",
+            "metric_units = true\nname = true\n[types]\nlock = \"*.lock\"\n",
+        );
+        let args = parse_args_from(vec!["siz", "."], &[user, project])?;
+
+        // the project-local options replace the conflicting user-level options
+        assert_eq!(args.binary_units, false);
+        assert_eq!(args.metric_units, true);
+        assert_eq!(args.highlow, false);
+        assert_eq!(args.name, true);
+        assert_eq!(args.depth, Some(2));
+        let type_names: Vec<&str> = args.type_add.iter().map(|ty| ty.name.as_str()).collect();
+        // the types tables are merged by type alias name
+        assert_eq!(type_names, vec!["lock", "weights"]);
+        assert_eq!(args.type_add[0].globs, vec!["*.lock"]);
+        assert_eq!(args.type_add[1].globs, vec!["*.pt"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_project_config_path() -> Result<()> {
+        let td = TempDir::new()?;
+        let nested = td.path().join("a").join("b");
+        std::fs::create_dir_all(&nested)?;
+        let config = write_config(&td, CONFIG_FILE_NAME, "");
+
+        assert_eq!(project_config_path(&nested), Some(config));
+        Ok(())
+    }
+}
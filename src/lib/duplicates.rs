@@ -0,0 +1,336 @@
+//! This module provides support for finding duplicate files.
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::record::FileRecord;
+
+/// The number of bytes at the start of a file that are hashed in the partial hash stage.
+const PARTIAL_HASH_SIZE: u64 = 4096;
+
+/// The read buffer size of the full hash stage.
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// `DuplicateSet` is a struct that represents a set of files with identical contents.
+///
+/// # Fields
+///
+/// * `size`: The apparent size of each file in the set in bytes.
+/// * `reclaimable`: The number of bytes that are reclaimed when all but the first file
+///   in the set are removed.  This is the sum of the report sizes of the other files,
+///   i.e., their allocated disk usage in disk usage mode.
+/// * `paths`: The file paths in the set, in sorted order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateSet {
+    pub size: u64,
+    pub reclaimable: u64,
+    pub paths: Vec<PathBuf>,
+}
+
+/// A duplicate file candidate with the (apparent size, content hash, path, report size)
+/// of the file.  Candidates sort in (apparent size, content hash, path) order.
+type Candidate = (u64, u128, PathBuf, u64);
+
+/// Finds the sets of files with identical contents in the `records`.
+///
+/// Duplicate detection runs in three stages that each narrow the candidate sets:
+///
+/// 1. files are grouped by apparent size
+/// 2. files of the same size are grouped by a hash of the first 4 KiB of the file
+/// 3. files with the same partial hash are grouped by a hash of the full file
+///
+/// The files are grouped by their apparent size in every size mode, because files
+/// with identical contents can have different allocated sizes, and files with different
+/// contents can have the same allocated size.  The hashes are 128-bit XXH3 hashes that
+/// are calculated in parallel.  Empty files are not reported as duplicates, and the hard
+/// links of a file that was already found through another hard link are excluded because
+/// removing them does not reclaim space.  Files that cannot be read are reported on
+/// stderr and are excluded from the duplicate sets.
+///
+/// # Returns
+///
+/// The duplicate sets, in ascending (size, first path) order.
+///
+/// # Examples
+///
+/// ```
+/// use siz::duplicates::find_duplicates;
+/// use siz::record::FileRecord;
+///
+/// let td = tempfile::TempDir::new().unwrap();
+/// let records: Vec<FileRecord> = ["a.txt", "b.txt"]
+///     .iter()
+///     .map(|name| {
+///         let path = td.path().join(name);
+///         std::fs::write(&path, "same").unwrap();
+///         let metadata = std::fs::metadata(&path).unwrap();
+///         FileRecord::new(path, &metadata)
+///     })
+///     .collect();
+/// let duplicate_sets = find_duplicates(records);
+/// assert_eq!(duplicate_sets.len(), 1);
+/// assert_eq!(duplicate_sets[0].reclaimable, 4);
+/// ```
+pub fn find_duplicates(records: Vec<FileRecord>) -> Vec<DuplicateSet> {
+    // stage 1: group by apparent size, without repeated hard links
+    let mut candidates: Vec<Candidate> = exclude_repeated_links(records)
+        .into_iter()
+        .map(|record| {
+            let length = record.apparent.unwrap_or(record.size);
+            (length, 0, record.path, record.size)
+        })
+        .filter(|(length, _, _, _)| *length > 0)
+        .collect();
+    candidates.par_sort_unstable();
+    let candidates = retain_duplicate_keys(candidates);
+
+    // stage 2: group by partial hash
+    let candidates = retain_duplicate_keys(hash_candidates(candidates, Some(PARTIAL_HASH_SIZE)));
+
+    // stage 3: group by full hash, the partial hash is a full
+    // hash of files that are not larger than the partial hash size
+    let (small, large): (Vec<_>, Vec<_>) = candidates
+        .into_iter()
+        .partition(|(length, _, _, _)| *length <= PARTIAL_HASH_SIZE);
+    let mut candidates = retain_duplicate_keys(hash_candidates(large, None));
+    candidates.extend(small);
+    candidates.par_sort_unstable();
+
+    let mut duplicate_sets: Vec<DuplicateSet> = Vec::new();
+    let mut last_key: Option<(u64, u128)> = None;
+    for (length, hash, path, size) in candidates {
+        match duplicate_sets.last_mut() {
+            Some(duplicate_set) if last_key == Some((length, hash)) => {
+                duplicate_set.paths.push(path);
+                duplicate_set.reclaimable += size;
+            }
+            _ => duplicate_sets.push(DuplicateSet {
+                size: length,
+                reclaimable: 0,
+                paths: vec![path],
+            }),
+        }
+        last_key = Some((length, hash));
+    }
+    duplicate_sets.sort_by(|a, b| (a.size, &a.paths).cmp(&(b.size, &b.paths)));
+    duplicate_sets
+}
+
+/// Returns the `records` without the records of a (device, inode) pair that was already
+/// seen in a record with a lower path.  Records without a device or inode are retained.
+fn exclude_repeated_links(mut records: Vec<FileRecord>) -> Vec<FileRecord> {
+    records.par_sort_unstable_by(|a, b| a.path.cmp(&b.path));
+    let mut seen: HashSet<(u64, u64)> = HashSet::new();
+    records
+        .into_iter()
+        .filter(|record| match (record.device, record.inode) {
+            (Some(device), Some(inode)) => seen.insert((device, inode)),
+            _ => true,
+        })
+        .collect()
+}
+
+/// Returns the candidates with the hash replaced by a hash of the file contents, in
+/// sorted order.  The hash includes up to `limit` bytes of the file contents, or the
+/// full file contents when `limit` is `None`.
+fn hash_candidates(candidates: Vec<Candidate>, limit: Option<u64>) -> Vec<Candidate> {
+    let mut hashed: Vec<Candidate> = candidates
+        .into_par_iter()
+        .filter_map(|(length, _, path, size)| match hash_file(&path, limit) {
+            Ok(hash) => Some((length, hash, path, size)),
+            Err(e) => {
+                eprintln!("Error reading file {}: {}", path.display(), e);
+                None
+            }
+        })
+        .collect();
+    hashed.par_sort_unstable();
+    hashed
+}
+
+/// Returns the sorted candidates without the candidates that have a unique
+/// (apparent size, hash) key.
+fn retain_duplicate_keys(candidates: Vec<Candidate>) -> Vec<Candidate> {
+    let keys: Vec<(u64, u128)> = candidates
+        .iter()
+        .map(|(length, hash, _, _)| (*length, *hash))
+        .collect();
+    candidates
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| (*i > 0 && keys[i - 1] == keys[*i]) || keys.get(i + 1) == Some(&keys[*i]))
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Returns the 128-bit XXH3 hash of up to `limit` bytes of the file at `path`, or
+/// of the full file when `limit` is `None`.
+fn hash_file(path: &Path, limit: Option<u64>) -> std::io::Result<u128> {
+    let file = File::open(path)?;
+    let mut reader: Box<dyn Read> = match limit {
+        Some(limit) => Box::new(file.take(limit)),
+        None => Box::new(file),
+    };
+    let mut hasher = Xxh3::new();
+    let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => hasher.update(&buffer[..n]),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(hasher.digest128())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn write_records(td: &TempDir, files: &[(&str, &[u8])]) -> Vec<FileRecord> {
+        files
+            .iter()
+            .map(|(name, contents)| {
+                let path = td.path().join(name);
+                std::fs::write(&path, contents).unwrap();
+                FileRecord::from_size(contents.len() as u64, path)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let td = TempDir::new().unwrap();
+        // large files that share the partial hash prefix and differ in the last byte
+        let mut large_a = vec![b'x'; 10_000];
+        let mut large_b = large_a.clone();
+        large_a.push(b'a');
+        large_b.push(b'b');
+        let records = write_records(
+            &td,
+            &[
+                ("one.txt", b"same"),
+                ("two.txt", b"same"),
+                ("three.txt", b"diff"),
+                ("four.txt", b"other size"),
+                ("empty1.txt", b""),
+                ("empty2.txt", b""),
+                ("large1.bin", &large_a),
+                ("large2.bin", &large_a),
+                ("large3.bin", &large_b),
+            ],
+        );
+        let duplicate_sets = find_duplicates(records);
+        assert_eq!(
+            duplicate_sets,
+            vec![
+                DuplicateSet {
+                    size: 4,
+                    reclaimable: 4,
+                    paths: vec![td.path().join("one.txt"), td.path().join("two.txt")],
+                },
+                DuplicateSet {
+                    size: 10_001,
+                    reclaimable: 10_001,
+                    paths: vec![td.path().join("large1.bin"), td.path().join("large2.bin")],
+                },
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_duplicates_sparse_files() -> std::io::Result<()> {
+        use crate::record::SizeMode;
+        use std::io::{Seek, SeekFrom, Write};
+
+        // sparse files with the same allocated size that differ after the partial
+        // hash prefix, and a sparse copy of one of them
+        let td = TempDir::new()?;
+        let mut records = Vec::new();
+        for (name, contents) in [("a.bin", b"AAAA"), ("b.bin", b"BBBB"), ("c.bin", b"AAAA")] {
+            let path = td.path().join(name);
+            let mut file = File::create(&path)?;
+            file.seek(SeekFrom::Start(1024 * 1024))?;
+            file.write_all(contents)?;
+            drop(file);
+            let metadata = std::fs::metadata(&path)?;
+            records.push(FileRecord::with_size_mode(
+                path,
+                &metadata,
+                SizeMode::Allocated,
+            ));
+        }
+        let allocated = records[2].size;
+
+        let duplicate_sets = find_duplicates(records);
+        assert_eq!(
+            duplicate_sets,
+            vec![DuplicateSet {
+                size: 1024 * 1024 + 4,
+                reclaimable: allocated,
+                paths: vec![td.path().join("a.bin"), td.path().join("c.bin")],
+            }]
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_duplicates_hard_links() -> std::io::Result<()> {
+        let td = TempDir::new()?;
+        let path = td.path().join("one.txt");
+        std::fs::write(&path, "same")?;
+        std::fs::hard_link(&path, td.path().join("two.txt"))?;
+        let records = ["one.txt", "two.txt"]
+            .iter()
+            .map(|name| {
+                let path = td.path().join(name);
+                let metadata = std::fs::metadata(&path)?;
+                Ok(FileRecord::new(path, &metadata))
+            })
+            .collect::<std::io::Result<Vec<FileRecord>>>()?;
+
+        // hard links to the same file are not reclaimable duplicates
+        assert_eq!(find_duplicates(records), Vec::new());
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_duplicates_unreadable_file() {
+        let td = TempDir::new().unwrap();
+        let mut records = write_records(&td, &[("one.txt", b"same"), ("two.txt", b"same")]);
+        records.push(FileRecord::from_size(4, td.path().join("missing.txt")));
+        let duplicate_sets = find_duplicates(records);
+        assert_eq!(duplicate_sets.len(), 1);
+        assert_eq!(duplicate_sets[0].paths.len(), 2);
+    }
+
+    #[test]
+    fn test_retain_duplicate_keys() {
+        let candidates = vec![
+            (1, 0, PathBuf::from("a"), 1),
+            (2, 0, PathBuf::from("b"), 2),
+            (2, 0, PathBuf::from("c"), 2),
+            (2, 1, PathBuf::from("d"), 2),
+            (3, 0, PathBuf::from("e"), 3),
+            (3, 0, PathBuf::from("f"), 3),
+            (3, 0, PathBuf::from("g"), 3),
+        ];
+        let retained: Vec<PathBuf> = retain_duplicate_keys(candidates)
+            .into_iter()
+            .map(|(_, _, path, _)| path)
+            .collect();
+        assert_eq!(
+            retained,
+            ["b", "c", "e", "f", "g"].map(PathBuf::from).to_vec()
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! This module provides support for excluding paths with glob patterns.
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+use crate::args::Args;
+
+/// `ExcludeMatcher` is a struct that matches the paths to exclude from a report.
+///
+/// The exclude patterns use the gitignore glob syntax and are relative to the report
+/// root path.  Unlike the glob option patterns, exclude patterns only remove paths.
+/// A set of exclude patterns never restricts a report to the matching paths, and a
+/// pattern that starts with `!` re-includes the paths that an earlier exclude pattern
+/// matched.
+///
+/// # Fields
+///
+/// * `matcher`: An `ignore::gitignore::Gitignore` matcher of the exclude patterns.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+///
+/// use siz::exclude::ExcludeMatcher;
+///
+/// let patterns = vec!["target/".to_string(), "*.log".to_string()];
+/// let matcher = ExcludeMatcher::new(Path::new("."), &patterns).unwrap();
+/// assert!(matcher.is_excluded(Path::new("./target"), true));
+/// assert!(matcher.is_excluded(Path::new("./logs/build.log"), false));
+/// assert!(!matcher.is_excluded(Path::new("./src/main.rs"), false));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExcludeMatcher {
+    matcher: Gitignore,
+}
+
+impl ExcludeMatcher {
+    /// Constructs a new `ExcludeMatcher` of the exclude `patterns` relative to `root`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when a pattern is not a valid glob pattern.
+    pub fn new(root: &Path, patterns: &[String]) -> Result<Self> {
+        let mut builder = GitignoreBuilder::new(root);
+        for pattern in patterns {
+            builder
+                .add_line(None, pattern)
+                .with_context(|| format!("invalid exclude pattern: {}", pattern))?;
+        }
+        Ok(Self {
+            matcher: builder.build()?,
+        })
+    }
+
+    /// Constructs an `ExcludeMatcher` from the exclude and exclude-from command line
+    /// arguments.
+    ///
+    /// The exclude patterns of the files are added after the command line patterns, and
+    /// the patterns are relative to the first path argument.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` that contains `None` when no exclude patterns are defined.
+    pub fn from_args(args: &Args) -> Result<Option<Self>> {
+        let mut patterns = args.exclude.clone();
+        for path in &args.exclude_from {
+            patterns.extend(read_exclude_file(path)?);
+        }
+        if patterns.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self::new(&args.paths[0], &patterns)?))
+    }
+
+    /// Returns `true` when the file or directory `path` is excluded.
+    #[inline(always)]
+    pub fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        self.matcher.matched(path, is_dir).is_ignore()
+    }
+}
+
+/// Returns the exclude patterns of the file at `path`, one pattern per line.  Blank
+/// lines and lines that start with `#` are skipped.
+fn read_exclude_file(path: &Path) -> Result<Vec<String>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("unable to read exclude file: {}", path.display()))?;
+    Ok(contents
+        .lines()
+        .map(|line| line.trim_end())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_exclude_matcher_patterns() -> Result<()> {
+        let patterns = ["target/", "*.log", "!keep.log", "/build"].map(String::from);
+        let matcher = ExcludeMatcher::new(Path::new("root"), &patterns)?;
+
+        assert!(matcher.is_excluded(Path::new("root/target"), true));
+        assert!(matcher.is_excluded(Path::new("root/a/target"), true));
+        assert!(!matcher.is_excluded(Path::new("root/target"), false));
+        assert!(matcher.is_excluded(Path::new("root/a/b.log"), false));
+        assert!(!matcher.is_excluded(Path::new("root/a/keep.log"), false));
+        assert!(matcher.is_excluded(Path::new("root/build"), true));
+        assert!(!matcher.is_excluded(Path::new("root/a/build"), true));
+        Ok(())
+    }
+
+    #[test]
+    fn test_exclude_matcher_from_args() -> Result<()> {
+        let td = TempDir::new()?;
+        let exclude_path = td.path().join("excludes");
+        std::fs::write(&exclude_path, "# generated files\n\n*.min.js\ndist/\n")?;
+        let exclude_path = exclude_path.to_str().unwrap();
+
+        assert_eq!(
+            read_exclude_file(Path::new(exclude_path))?,
+            ["*.min.js", "dist/"]
+        );
+
+        let args = Args::parse_from(vec!["siz", "root"]);
+        assert!(ExcludeMatcher::from_args(&args)?.is_none());
+
+        let args = Args::parse_from(vec![
+            "siz",
+            "--exclude",
+            "*.log",
+            "--exclude-from",
+            exclude_path,
+            "root",
+        ]);
+        let matcher = ExcludeMatcher::from_args(&args)?.unwrap();
+        assert!(matcher.is_excluded(Path::new("root/a.log"), false));
+        assert!(matcher.is_excluded(Path::new("root/app.min.js"), false));
+        assert!(matcher.is_excluded(Path::new("root/dist"), true));
+        assert!(!matcher.is_excluded(Path::new("root/app.js"), false));
+
+        let args = Args::parse_from(vec!["siz", "--exclude-from", "missing", "root"]);
+        assert!(ExcludeMatcher::from_args(&args).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,250 @@
+//! This module provides support for reporting the files of a file list instead of the
+//! files of a directory walk.
+use std::fs::{File, Metadata};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::args::Args;
+use crate::filter::EntryFilter;
+
+/// The files-from option path that reads the file list from the standard input stream.
+pub const STDIN_PATH: &str = "-";
+
+/// `FileList` is a struct that yields the files of a file list in list order.
+///
+/// The listed paths are not walked.  Paths that are not files (e.g., directories) are
+/// skipped, paths that cannot be read are reported on stderr and skipped, and the file
+/// size and modification time filters apply to the listed files.
+///
+/// # Fields
+///
+/// * `paths`: The remaining paths of the file list.
+/// * `follow`: A boolean that indicates whether symbolic links are followed.
+/// * `entry_filter`: The `EntryFilter` of the file metadata filters.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+///
+/// use clap::Parser;
+///
+/// use siz::args::Args;
+/// use siz::filelist::FileList;
+///
+/// let args = Args::parse_from(vec!["siz", "--files-from", "-"]);
+/// let paths = vec![PathBuf::from("Cargo.toml"), PathBuf::from("src")];
+/// let files: Vec<PathBuf> = FileList::new(paths, &args)
+///     .unwrap()
+///     .map(|(path, _)| path)
+///     .collect();
+/// assert_eq!(files, vec![PathBuf::from("Cargo.toml")]);
+/// ```
+#[derive(Debug)]
+pub struct FileList {
+    paths: std::vec::IntoIter<PathBuf>,
+    follow: bool,
+    entry_filter: EntryFilter,
+}
+
+impl FileList {
+    /// Constructs a new `FileList` of the `paths` with the follow and file metadata
+    /// filter command line arguments.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file metadata filters are invalid.
+    pub fn new(paths: Vec<PathBuf>, args: &Args) -> Result<Self> {
+        Ok(Self {
+            paths: paths.into_iter(),
+            follow: args.follow,
+            entry_filter: EntryFilter::from_args(args)?,
+        })
+    }
+
+    /// Constructs a `FileList` from the files-from and from0 command line arguments.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` that contains `None` when the files-from option is not defined.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file list cannot be read.
+    pub fn from_args(args: &Args) -> Result<Option<Self>> {
+        let Some(list_path) = &args.files_from else {
+            return Ok(None);
+        };
+        let delimiter = if args.from0 { b'\0' } else { b'\n' };
+        let paths = if list_path == Path::new(STDIN_PATH) {
+            read_file_list(std::io::stdin().lock(), delimiter)
+                .context("unable to read file list from standard input")?
+        } else {
+            File::open(list_path)
+                .map_err(anyhow::Error::new)
+                .and_then(|file| read_file_list(BufReader::new(file), delimiter))
+                .with_context(|| format!("unable to read file list: {}", list_path.display()))?
+        };
+        Ok(Some(Self::new(paths, args)?))
+    }
+}
+
+impl Iterator for FileList {
+    type Item = (PathBuf, Metadata);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for path in self.paths.by_ref() {
+            let metadata = if self.follow {
+                std::fs::metadata(&path)
+            } else {
+                std::fs::symlink_metadata(&path)
+            };
+            match metadata {
+                Ok(metadata) => {
+                    // filter on file paths only, exclude all directory paths and the
+                    // symbolic links that are not followed
+                    if metadata.is_file() && self.entry_filter.matches(&metadata) {
+                        return Some((path, metadata));
+                    }
+                }
+                Err(e) => eprintln!("Error reading path: {}: {}", path.display(), e),
+            }
+        }
+        None
+    }
+}
+
+/// Returns the paths of a file list with `delimiter`-terminated paths, in list order.
+///
+/// Empty paths are skipped, and the carriage return of a CRLF line ending is removed
+/// from the paths of a newline-delimited file list.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+///
+/// use siz::filelist::read_file_list;
+///
+/// let paths = read_file_list("a.txt\0dir/b.txt\0".as_bytes(), b'\0').unwrap();
+/// assert_eq!(paths, vec![PathBuf::from("a.txt"), PathBuf::from("dir/b.txt")]);
+/// ```
+pub fn read_file_list<R: BufRead>(reader: R, delimiter: u8) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for item in reader.split(delimiter) {
+        let mut bytes = item?;
+        if delimiter == b'\n' && bytes.last() == Some(&b'\r') {
+            bytes.pop();
+        }
+        if !bytes.is_empty() {
+            paths.push(path_from_bytes(bytes)?);
+        }
+    }
+    Ok(paths)
+}
+
+/// Returns the path of the raw bytes of a file list path.
+#[cfg(unix)]
+fn path_from_bytes(bytes: Vec<u8>) -> Result<PathBuf> {
+    use std::os::unix::ffi::OsStringExt;
+    Ok(PathBuf::from(std::ffi::OsString::from_vec(bytes)))
+}
+
+/// Returns the path of the raw bytes of a file list path.  File list paths must be
+/// UTF-8 on platforms other than Unix.
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: Vec<u8>) -> Result<PathBuf> {
+    let path = String::from_utf8(bytes).context("file list path is not valid UTF-8")?;
+    Ok(PathBuf::from(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_read_file_list() -> Result<()> {
+        let newline_list = "a.txt\r\n\nb c.txt\ndir/d.txt";
+        assert_eq!(
+            read_file_list(newline_list.as_bytes(), b'\n')?,
+            ["a.txt", "b c.txt", "dir/d.txt"].map(PathBuf::from)
+        );
+
+        // NUL-delimited paths can contain newlines
+        let nul_list = "a\nb.txt\0\0c.txt\0";
+        assert_eq!(
+            read_file_list(nul_list.as_bytes(), b'\0')?,
+            ["a\nb.txt", "c.txt"].map(PathBuf::from)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_list_from_args() -> Result<()> {
+        let td = TempDir::new()?;
+        let small = td.path().join("small.txt");
+        let large = td.path().join("large.txt");
+        std::fs::write(&small, "1")?;
+        std::fs::write(&large, "1234567890")?;
+        let list_path = td.path().join("list.txt");
+        let list = [
+            large.to_str().unwrap(),
+            td.path().to_str().unwrap(),
+            td.path().join("missing.txt").to_str().unwrap(),
+            small.to_str().unwrap(),
+        ]
+        .join("\0");
+        std::fs::write(&list_path, list)?;
+        let list_path = list_path.to_str().unwrap();
+
+        let args = Args::parse_from(vec!["siz", "--files-from", list_path, "--from0"]);
+        let files: Vec<PathBuf> = FileList::from_args(&args)?
+            .unwrap()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(files, vec![large.clone(), small.clone()]);
+
+        let args = Args::parse_from(vec![
+            "siz",
+            "--files-from",
+            list_path,
+            "--from0",
+            "--min-size",
+            "5B",
+        ]);
+        let files: Vec<PathBuf> = FileList::from_args(&args)?
+            .unwrap()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(files, vec![large]);
+
+        // symbolic links are reported only when they are followed
+        #[cfg(unix)]
+        {
+            let link = td.path().join("link.txt");
+            std::os::unix::fs::symlink(&small, &link)?;
+            let list_path = td.path().join("links.txt");
+            std::fs::write(&list_path, link.to_str().unwrap())?;
+            let list_path = list_path.to_str().unwrap();
+
+            let args = Args::parse_from(vec!["siz", "--files-from", list_path]);
+            assert_eq!(FileList::from_args(&args)?.unwrap().count(), 0);
+            let args = Args::parse_from(vec!["siz", "--files-from", list_path, "-L"]);
+            let files: Vec<PathBuf> = FileList::from_args(&args)?
+                .unwrap()
+                .map(|(path, _)| path)
+                .collect();
+            assert_eq!(files, vec![link]);
+        }
+
+        assert!(FileList::from_args(&Args::parse_from(vec!["siz", "."]))?.is_none());
+        let args = Args::parse_from(vec!["siz", "--files-from", "missing"]);
+        assert!(FileList::from_args(&args).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,516 @@
+//! This module provides support for file metadata filters and the parsing of
+//! human-readable filter values.
+use std::fs::Metadata;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+
+use crate::args::Args;
+use crate::format::{civil_from_days, days_from_civil, format_timestamp};
+use crate::record::SizeMode;
+
+/// Parses a human-readable size string into a size in bytes.
+///
+/// The size is a non-negative integer or decimal number with an optional unit suffix.
+/// Whitespace between the number and the unit is optional, and units are case-insensitive.
+/// The units mirror the SI metric and binary units in the human-readable size output formats:
+///
+/// * bytes: `B` (or no unit)
+/// * SI metric units: `kB`, `MB`, `GB`, `TB`, `PB`, `EB` (also `k`, `M`, `G`, ...)
+/// * binary units: `KiB`, `MiB`, `GiB`, `TiB`, `PiB`, `EiB` (also `Ki`, `Mi`, `Gi`, ...)
+///
+/// # Errors
+///
+/// Returns an error string if the size string cannot be parsed or the size overflows
+/// a `u64`.
+///
+/// # Examples
+///
+/// ```
+/// use siz::filter::parse_size;
+///
+/// assert_eq!(parse_size("512"), Ok(512));
+/// assert_eq!(parse_size("10MiB"), Ok(10 * 1024 * 1024));
+/// assert_eq!(parse_size("1 GB"), Ok(1_000_000_000));
+/// assert_eq!(parse_size("9.77 KiB"), Ok(10004));
+/// assert!(parse_size("10 parsecs").is_err());
+/// ```
+pub fn parse_size(size: &str) -> Result<u64, String> {
+    let size = size.trim();
+    let unit_index = size
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(unit_index);
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1000,
+        "m" | "mb" => 1000u64.pow(2),
+        "g" | "gb" => 1000u64.pow(3),
+        "t" | "tb" => 1000u64.pow(4),
+        "p" | "pb" => 1000u64.pow(5),
+        "e" | "eb" => 1000u64.pow(6),
+        "ki" | "kib" => 1 << 10,
+        "mi" | "mib" => 1 << 20,
+        "gi" | "gib" => 1 << 30,
+        "ti" | "tib" => 1 << 40,
+        "pi" | "pib" => 1 << 50,
+        "ei" | "eib" => 1 << 60,
+        _ => {
+            return Err(format!(
+            "invalid size unit '{}'. Use B, kB, MB, GB, TB, PB, EB or KiB, MiB, GiB, TiB, PiB, EiB",
+            unit.trim()
+        ))
+        }
+    };
+    if number.contains('.') {
+        let value: f64 = number
+            .parse()
+            .map_err(|_| format!("invalid size number '{}'", number))?;
+        let bytes = (value * multiplier as f64).round();
+        if bytes >= u64::MAX as f64 {
+            return Err(format!("size is too large: {}", size));
+        }
+        Ok(bytes as u64)
+    } else {
+        let value: u64 = number
+            .parse()
+            .map_err(|_| format!("invalid size number '{}'", number))?;
+        value
+            .checked_mul(multiplier)
+            .ok_or_else(|| format!("size is too large: {}", size))
+    }
+}
+
+/// Parses a file age or an absolute date into a modification time cutoff.
+///
+/// The time is either an age relative to the current time, or an absolute date.
+/// Ages are non-negative integers with a unit suffix, and whitespace between the
+/// number and the unit is optional:
+///
+/// * seconds: `s`, `sec`, `secs`
+/// * minutes: `m`, `min`, `mins`
+/// * hours: `h`, `hr`, `hrs`, `hour`, `hours`
+/// * days: `d`, `day`, `days`
+/// * weeks: `w`, `week`, `weeks`
+/// * years: `y`, `year`, `years` (365 days)
+///
+/// Absolute dates are in the `YYYY-MM-DD`, `YYYY-MM-DDTHH:MM`, or `YYYY-MM-DDTHH:MM:SS`
+/// formats, with an optional trailing `Z`.  A space may replace the `T` separator.
+/// Absolute dates are interpreted in UTC.
+///
+/// # Errors
+///
+/// Returns an error string if the time string cannot be parsed.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// use siz::filter::parse_time;
+///
+/// assert_eq!(
+///     parse_time("2023-11-14T22:13:20Z"),
+///     Ok(UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+/// );
+/// assert!(parse_time("30d").is_ok());
+/// assert!(parse_time("30 fortnights").is_err());
+/// ```
+pub fn parse_time(time: &str) -> Result<SystemTime, String> {
+    parse_time_relative_to(time, SystemTime::now())
+}
+
+/// Parses a time string into a modification time cutoff, with ages that are relative
+/// to `now`.
+fn parse_time_relative_to(time: &str, now: SystemTime) -> Result<SystemTime, String> {
+    let time = time.trim();
+    if time.len() >= 10 && time.as_bytes()[4] == b'-' {
+        return parse_date(time);
+    }
+    let unit_index = time
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(time.len());
+    let (number, unit) = time.split_at(unit_index);
+    let value: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid age or date '{}'", time))?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "s" | "sec" | "secs" => 1,
+        "m" | "min" | "mins" => 60,
+        "h" | "hr" | "hrs" | "hour" | "hours" => 60 * 60,
+        "d" | "day" | "days" => 24 * 60 * 60,
+        "w" | "week" | "weeks" => 7 * 24 * 60 * 60,
+        "y" | "year" | "years" => 365 * 24 * 60 * 60,
+        "" => {
+            return Err(format!(
+                "missing age unit in '{}'. Use s, m, h, d, w, or y",
+                time
+            ))
+        }
+        _ => {
+            return Err(format!(
+                "invalid age unit '{}'. Use s, m, h, d, w, or y",
+                unit.trim()
+            ))
+        }
+    };
+    value
+        .checked_mul(multiplier)
+        .and_then(|secs| now.checked_sub(Duration::from_secs(secs)))
+        .ok_or_else(|| format!("age is too large: {}", time))
+}
+
+/// Parses an absolute UTC date in the `YYYY-MM-DD[THH:MM[:SS]][Z]` format.
+fn parse_date(date: &str) -> Result<SystemTime, String> {
+    let invalid = || {
+        format!(
+            "invalid date '{}'. Use YYYY-MM-DD, YYYY-MM-DDTHH:MM, or YYYY-MM-DDTHH:MM:SS",
+            date
+        )
+    };
+    let trimmed = date.strip_suffix(['Z', 'z']).unwrap_or(date);
+    let (day_part, time_part) = match trimmed.split_once(['T', 't', ' ']) {
+        Some((day_part, time_part)) => (day_part, Some(time_part)),
+        None => (trimmed, None),
+    };
+    let ymd: Vec<&str> = day_part.split('-').collect();
+    if ymd.len() != 3 || ymd[0].len() != 4 || ymd[1].len() != 2 || ymd[2].len() != 2 {
+        return Err(invalid());
+    }
+    let year: i64 = ymd[0].parse().map_err(|_| invalid())?;
+    let month: u32 = ymd[1].parse().map_err(|_| invalid())?;
+    let day: u32 = ymd[2].parse().map_err(|_| invalid())?;
+    let days = days_from_civil(year, month, day);
+    // reject out of range months and days, like 2023-02-30
+    if civil_from_days(days) != (year, month, day) {
+        return Err(invalid());
+    }
+    let secs_of_day = match time_part {
+        Some(time_part) => {
+            let hms: Vec<&str> = time_part.split(':').collect();
+            if !(2..=3).contains(&hms.len()) || hms.iter().any(|field| field.len() != 2) {
+                return Err(invalid());
+            }
+            let mut fields = [0u64; 3];
+            for (field, value) in fields.iter_mut().zip(&hms) {
+                *field = value.parse().map_err(|_| invalid())?;
+            }
+            let [hours, minutes, seconds] = fields;
+            if hours > 23 || minutes > 59 || seconds > 59 {
+                return Err(invalid());
+            }
+            hours * 3600 + minutes * 60 + seconds
+        }
+        None => 0,
+    };
+    if days < 0 {
+        return Err(format!("date is before 1970-01-01: {}", date));
+    }
+    Ok(UNIX_EPOCH + Duration::from_secs(days as u64 * 86_400 + secs_of_day))
+}
+
+/// `EntryFilter` is a struct that filters walked file entries on their file system metadata.
+///
+/// Directory entries pass the filter so that the walk descends into them, unless they
+/// are on a different device than the walk root in one file system mode.
+///
+/// # Fields
+///
+/// * `min_size`: An optional minimum file size in bytes.
+/// * `max_size`: An optional maximum file size in bytes.
+/// * `older_than`: An optional modification time cutoff, files must be modified before it.
+/// * `newer_than`: An optional modification time cutoff, files must be modified after it.
+/// * `size_mode`: The `SizeMode` of the file sizes that the size filters apply to.
+/// * `root_device`: An optional device ID of the walk root path.  Entries on other devices,
+///   including directories that are mount points, fail the filter.
+///
+/// # Examples
+///
+/// ```
+/// use clap::Parser;
+///
+/// use siz::args::Args;
+/// use siz::filter::EntryFilter;
+///
+/// let args = Args::parse_from(vec!["siz", "--min-size", "1B", "."]);
+/// let filter = EntryFilter::from_args(&args).unwrap();
+/// let metadata = std::fs::metadata("Cargo.toml").unwrap();
+/// assert!(filter.matches(&metadata));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EntryFilter {
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub older_than: Option<SystemTime>,
+    pub newer_than: Option<SystemTime>,
+    pub size_mode: SizeMode,
+    pub root_device: Option<u64>,
+}
+
+impl EntryFilter {
+    /// Constructs a new `EntryFilter` from the command line arguments.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the minimum size is larger than the maximum size, if
+    /// the modification time filters do not overlap, or if the metadata of the walk
+    /// root path cannot be read in one file system mode.
+    pub fn from_args(args: &Args) -> Result<Self> {
+        if let (Some(min_size), Some(max_size)) = (args.min_size, args.max_size) {
+            if min_size > max_size {
+                anyhow::bail!(
+                    "the minimum size filter ({} bytes) is larger than the maximum size filter ({} bytes)",
+                    min_size,
+                    max_size
+                );
+            }
+        }
+        if let (Some(older_than), Some(newer_than)) = (args.older_than, args.newer_than) {
+            if newer_than >= older_than {
+                anyhow::bail!(
+                    "the --newer-than filter ({}) is not earlier than the --older-than filter ({})",
+                    format_timestamp(newer_than),
+                    format_timestamp(older_than)
+                );
+            }
+        }
+        // the device is only available on Unix platforms. The walker
+        // falls back to the ignore crate same file system option elsewhere.
+        let root_device = match (args.paths.first(), args.one_file_system) {
+            #[cfg(unix)]
+            (Some(path), true) => {
+                use std::os::unix::fs::MetadataExt;
+                Some(std::fs::metadata(path)?.dev())
+            }
+            _ => None,
+        };
+        Ok(Self {
+            min_size: args.min_size,
+            max_size: args.max_size,
+            older_than: args.older_than,
+            newer_than: args.newer_than,
+            size_mode: SizeMode::from_args(args),
+            root_device,
+        })
+    }
+
+    /// Returns `true` if at least one filter is defined.
+    pub fn is_active(&self) -> bool {
+        self.min_size.is_some()
+            || self.max_size.is_some()
+            || self.older_than.is_some()
+            || self.newer_than.is_some()
+            || self.root_device.is_some()
+    }
+
+    /// Returns `true` if the entry with `metadata` passes the filter.
+    #[inline(always)]
+    pub fn matches(&self, metadata: &Metadata) -> bool {
+        #[cfg(unix)]
+        if let Some(root_device) = self.root_device {
+            use std::os::unix::fs::MetadataExt;
+            if metadata.dev() != root_device {
+                return false;
+            }
+        }
+        if metadata.is_dir() {
+            return true;
+        }
+        self.matches_file(self.size_mode.size(metadata), metadata.modified().ok())
+    }
+
+    /// Returns `true` if a file with `size` and the optional `modified` time passes
+    /// the filter.  Files without a modification time fail the time filters.
+    #[inline(always)]
+    fn matches_file(&self, size: u64, modified: Option<SystemTime>) -> bool {
+        self.min_size.map_or(true, |min_size| size >= min_size)
+            && self.max_size.map_or(true, |max_size| size <= max_size)
+            && self.older_than.map_or(true, |older_than| {
+                modified.is_some_and(|modified| modified < older_than)
+            })
+            && self.newer_than.map_or(true, |newer_than| {
+                modified.is_some_and(|modified| modified > newer_than)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_parse_size_bytes() {
+        assert_eq!(parse_size("0"), Ok(0));
+        assert_eq!(parse_size("100"), Ok(100));
+        assert_eq!(parse_size("100B"), Ok(100));
+        assert_eq!(parse_size(" 100 b "), Ok(100));
+    }
+
+    #[test]
+    fn test_parse_size_metric_units() {
+        assert_eq!(parse_size("1kB"), Ok(1000));
+        assert_eq!(parse_size("1 KB"), Ok(1000));
+        assert_eq!(parse_size("1k"), Ok(1000));
+        assert_eq!(parse_size("10 MB"), Ok(10_000_000));
+        assert_eq!(parse_size("1GB"), Ok(1_000_000_000));
+        assert_eq!(parse_size("2TB"), Ok(2_000_000_000_000));
+        assert_eq!(parse_size("1PB"), Ok(1_000_000_000_000_000));
+        assert_eq!(parse_size("1EB"), Ok(1_000_000_000_000_000_000));
+        assert_eq!(parse_size("1.5 kB"), Ok(1500));
+    }
+
+    #[test]
+    fn test_parse_size_binary_units() {
+        assert_eq!(parse_size("1KiB"), Ok(1024));
+        assert_eq!(parse_size("1 kib"), Ok(1024));
+        assert_eq!(parse_size("10MiB"), Ok(10_485_760));
+        assert_eq!(parse_size("1Gi"), Ok(1_073_741_824));
+        assert_eq!(parse_size("1TiB"), Ok(1 << 40));
+        assert_eq!(parse_size("1PiB"), Ok(1 << 50));
+        assert_eq!(parse_size("1EiB"), Ok(1 << 60));
+        assert_eq!(parse_size("1.5 KiB"), Ok(1536));
+    }
+
+    #[test]
+    fn test_parse_size_round_trips_formatter_output() {
+        use crate::format::{build_binary_size_formatter, build_metric_size_formatter};
+
+        let msf = build_metric_size_formatter();
+        let bsf = build_binary_size_formatter();
+        assert_eq!(parse_size(&msf(10_000_000)), Ok(10_000_000));
+        assert_eq!(parse_size(&bsf(10_485_760)), Ok(10_485_760));
+    }
+
+    #[test]
+    fn test_parse_size_errors() {
+        assert!(parse_size("").is_err());
+        assert!(parse_size("MB").is_err());
+        assert!(parse_size("-1").is_err());
+        assert!(parse_size("1.2.3 MB").is_err());
+        assert!(parse_size("10 XB").is_err());
+        assert!(parse_size("100EiB").is_err());
+    }
+
+    #[test]
+    fn test_entry_filter_from_args() -> Result<()> {
+        let args = Args::parse_from(vec!["siz", "."]);
+        assert!(!EntryFilter::from_args(&args)?.is_active());
+
+        let args = Args::parse_from(vec!["siz", "--min-size", "1kB", "--max-size", "1MiB", "."]);
+        let filter = EntryFilter::from_args(&args)?;
+        assert!(filter.is_active());
+        assert_eq!(filter.min_size, Some(1000));
+        assert_eq!(filter.max_size, Some(1_048_576));
+
+        let args = Args::parse_from(vec!["siz", "--min-size", "2kB", "--max-size", "1kB", "."]);
+        assert!(EntryFilter::from_args(&args).is_err());
+
+        let args = Args::parse_from(vec![
+            "siz",
+            "--newer-than",
+            "30d",
+            "--older-than",
+            "7d",
+            ".",
+        ]);
+        assert!(EntryFilter::from_args(&args)?.is_active());
+        let args = Args::parse_from(vec![
+            "siz",
+            "--newer-than",
+            "7d",
+            "--older-than",
+            "30d",
+            ".",
+        ]);
+        assert!(EntryFilter::from_args(&args).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_time_ages() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        let ago = |secs: u64| Ok(now - Duration::from_secs(secs));
+        assert_eq!(parse_time_relative_to("0s", now), ago(0));
+        assert_eq!(parse_time_relative_to("45s", now), ago(45));
+        assert_eq!(parse_time_relative_to("15m", now), ago(15 * 60));
+        assert_eq!(parse_time_relative_to("6h", now), ago(6 * 3600));
+        assert_eq!(parse_time_relative_to("30d", now), ago(30 * 86_400));
+        assert_eq!(parse_time_relative_to("30 days", now), ago(30 * 86_400));
+        assert_eq!(parse_time_relative_to("2W", now), ago(14 * 86_400));
+        assert_eq!(parse_time_relative_to("1y", now), ago(365 * 86_400));
+    }
+
+    #[test]
+    fn test_parse_time_dates() {
+        let at = |secs: u64| Ok(UNIX_EPOCH + Duration::from_secs(secs));
+        assert_eq!(parse_time("1970-01-01"), at(0));
+        assert_eq!(parse_time("2000-02-29"), at(951_782_400));
+        assert_eq!(parse_time("2023-12-31T23:59:59Z"), at(1_704_067_199));
+        assert_eq!(parse_time("2023-12-31 23:59"), at(1_704_067_140));
+    }
+
+    #[test]
+    fn test_parse_time_errors() {
+        assert!(parse_time("").is_err());
+        assert!(parse_time("30").is_err());
+        assert!(parse_time("d").is_err());
+        assert!(parse_time("-1d").is_err());
+        assert!(parse_time("1.5d").is_err());
+        assert!(parse_time("30 parsecs").is_err());
+        assert!(parse_time("2023-02-30").is_err());
+        assert!(parse_time("2023-13-01").is_err());
+        assert!(parse_time("2023-1-1").is_err());
+        assert!(parse_time("2023-01-01T24:00").is_err());
+        assert!(parse_time("1969-12-31").is_err());
+        assert!(parse_time("99999999999999999999y").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_entry_filter_root_device() -> Result<()> {
+        use std::os::unix::fs::MetadataExt;
+
+        let args = Args::parse_from(vec!["siz", "--one-file-system", "."]);
+        let filter = EntryFilter::from_args(&args)?;
+        let dir_metadata = std::fs::metadata("src")?;
+        let file_metadata = std::fs::metadata("Cargo.toml")?;
+        assert!(filter.is_active());
+        assert_eq!(filter.root_device, Some(dir_metadata.dev()));
+        assert!(filter.matches(&dir_metadata));
+        assert!(filter.matches(&file_metadata));
+
+        let filter = EntryFilter {
+            root_device: Some(dir_metadata.dev().wrapping_add(1)),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&dir_metadata));
+        assert!(!filter.matches(&file_metadata));
+        Ok(())
+    }
+
+    #[test]
+    fn test_entry_filter_matches_file_times() {
+        let cutoff = UNIX_EPOCH + Duration::from_secs(1_000);
+        let before = Some(cutoff - Duration::from_secs(1));
+        let after = Some(cutoff + Duration::from_secs(1));
+
+        let filter = EntryFilter {
+            older_than: Some(cutoff),
+            ..Default::default()
+        };
+        assert!(filter.matches_file(0, before));
+        assert!(!filter.matches_file(0, Some(cutoff)));
+        assert!(!filter.matches_file(0, after));
+        assert!(!filter.matches_file(0, None));
+
+        let filter = EntryFilter {
+            newer_than: Some(cutoff),
+            min_size: Some(10),
+            ..Default::default()
+        };
+        assert!(filter.matches_file(10, after));
+        assert!(!filter.matches_file(9, after));
+        assert!(!filter.matches_file(10, before));
+    }
+}
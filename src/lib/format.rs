@@ -1,4 +1,6 @@
 //! This module provides functions for formatting the output.
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use humansize::{make_format, BINARY, DECIMAL};
 
 /// Returns a closure that formats a file size in SI metric units.
@@ -37,6 +39,71 @@ pub fn build_binary_size_formatter() -> impl Fn(u64) -> String {
     make_format(BINARY)
 }
 
+/// Formats a `SystemTime` as an RFC 3339 UTC timestamp string with second precision.
+///
+/// Times before the Unix epoch are clamped to the epoch.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// use siz::format::format_timestamp;
+///
+/// let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+/// assert_eq!(format_timestamp(time), "2023-11-14T22:13:20Z");
+/// ```
+pub fn format_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+    let (year, month, day) = civil_from_days(days as i64);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        (secs_of_day % 3600) / 60,
+        secs_of_day % 60
+    )
+}
+
+/// Converts a count of days since the Unix epoch to a proleptic Gregorian
+/// calendar (year, month, day) date.
+///
+/// See Howard Hinnant's `civil_from_days` algorithm:
+/// <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Converts a proleptic Gregorian calendar (year, month, day) date to a count of
+/// days since the Unix epoch.
+///
+/// See Howard Hinnant's `days_from_civil` algorithm:
+/// <https://howardhinnant.github.io/date_algorithms.html#days_from_civil>
+pub(crate) fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = i64::from((month + 9) % 12);
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -79,4 +146,30 @@ mod tests {
         assert_eq!(bsf(1000000000), "953.67 MiB");
         assert_eq!(bsf(1073742000), "1.00 GiB");
     }
+
+    #[test]
+    fn test_format_timestamp() {
+        use std::time::Duration;
+
+        assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH + Duration::from_secs(951_782_400)),
+            "2000-02-29T00:00:00Z"
+        );
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH + Duration::from_secs(1_704_067_199)),
+            "2023-12-31T23:59:59Z"
+        );
+    }
+
+    #[test]
+    fn test_days_from_civil_round_trip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 2, 29), 11_016);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        for days in [-719_468, -1, 0, 11_016, 19_722, 2_932_896] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
 }
@@ -37,8 +37,8 @@ pub fn levenshtein_distance(s1: &str, s2: &str) -> usize {
         return len_s1;
     }
     let mut d: [Vec<usize>; 2] = [vec![0; len_s2 + 1], vec![0; len_s2 + 1]];
-    for j in 0..=len_s2 {
-        d[0][j] = j;
+    for (j, item) in d[0].iter_mut().enumerate() {
+        *item = j;
     }
     for i in 1..=len_s1 {
         d[i % 2][0] = i;
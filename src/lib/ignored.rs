@@ -0,0 +1,426 @@
+//! This module provides support for classifying paths with the ignore file rules that
+//! the file system walker uses.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+
+use crate::args::Args;
+
+/// The by-ignore report class name of the files that the ignore file rules exclude.
+pub const IGNORED_CLASS: &str = "ignored";
+
+/// The by-ignore report class name of the files that the ignore file rules do not exclude.
+pub const TRACKED_CLASS: &str = "tracked";
+
+/// The default number of largest files of each class in the by-ignore report.
+pub const DEFAULT_CLASS_LARGEST_FILES: usize = 10;
+
+/// The ignore file matchers of a directory.
+///
+/// # Fields
+///
+/// * `ignore`: The matcher of the directory `.ignore` file, if present.
+/// * `gitignore`: The matcher of the directory `.gitignore` file, if present.
+/// * `has_git`: A boolean that indicates whether the directory is a git repository root.
+#[derive(Debug, Default)]
+struct DirMatchers {
+    ignore: Option<Gitignore>,
+    gitignore: Option<Gitignore>,
+    has_git: bool,
+}
+
+/// `IgnoreClassifier` is a struct that classifies the paths of a walk without ignore
+/// rules as ignored or not ignored.
+///
+/// The classifier applies the same ignore file rules as the default file system walk:
+///
+/// * `.ignore` files in the root directory, its parent directories, and its subdirectories
+/// * `.gitignore` files in git repositories, up to the git repository root
+/// * ignore files that are defined with the ignore-file option
+///
+/// A deeper ignore file takes precedence over the ignore files of its parent directories,
+/// `.ignore` files take precedence over `.gitignore` files, and `.gitignore` files take
+/// precedence over the ignore-file option files.  A path is ignored when the path or one
+/// of its parent directories below its walk root path is ignored, because the default walk
+/// does not descend into ignored directories.  The walk root paths are never ignored.
+///
+/// The classifier reads the ignore files with the `ignore` crate matchers and does not
+/// walk the file system, so paths that cannot be read are classified by their ignore
+/// rules.  The ignore file matchers of each directory are cached, and the classifier is
+/// safe to share across the threads of a parallel walk.
+///
+/// # Fields
+///
+/// * `roots`: The walk root paths and their absolute paths.
+/// * `git_ignore`: A boolean that indicates whether `.gitignore` files are respected.
+/// * `explicit`: The matchers of the ignore-file option files.
+/// * `dirs`: The cached ignore file matchers of each absolute directory path.
+/// * `ignored_dirs`: The cached ignored directory classifications of each directory path.
+#[derive(Debug)]
+pub struct IgnoreClassifier {
+    roots: Vec<(PathBuf, PathBuf)>,
+    git_ignore: bool,
+    explicit: Vec<Gitignore>,
+    dirs: Mutex<HashMap<PathBuf, Arc<DirMatchers>>>,
+    ignored_dirs: Mutex<HashMap<PathBuf, bool>>,
+}
+
+impl IgnoreClassifier {
+    /// Constructs a new `IgnoreClassifier` of the walk root paths with the no-ignore-vcs
+    /// and ignore-file command line arguments.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when a root path does not exist or an ignore-file option file
+    /// cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// use clap::Parser;
+    ///
+    /// use siz::args::Args;
+    /// use siz::ignored::IgnoreClassifier;
+    ///
+    /// let args = Args::parse_from(vec!["siz", "."]);
+    /// let classifier = IgnoreClassifier::new(&args).unwrap();
+    /// assert!(classifier.is_ignored(Path::new("./target"), true));
+    /// assert!(!classifier.is_ignored(Path::new("./Cargo.toml"), false));
+    /// ```
+    pub fn new(args: &Args) -> Result<Self> {
+        let mut roots = Vec::new();
+        for root in &args.paths {
+            let absolute_root = root
+                .canonicalize()
+                .with_context(|| format!("unable to read path: {}", root.display()))?;
+            roots.push((root.clone(), absolute_root));
+        }
+        let mut explicit = Vec::new();
+        for path in &args.ignore_file {
+            explicit.push(build_explicit_ignore(path)?);
+        }
+        Ok(Self {
+            roots,
+            git_ignore: !args.no_ignore_vcs,
+            explicit,
+            dirs: Mutex::new(HashMap::new()),
+            ignored_dirs: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Returns `true` when the file or directory `path` of the walk is ignored.
+    ///
+    /// The walk root path is never ignored.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let root = self.root_of(path);
+        if root.is_some_and(|(root, _)| path == root) {
+            return false;
+        }
+        if let Some(parent) = path.parent() {
+            if self.is_dir_ignored(parent) {
+                return true;
+            }
+        }
+        self.matched(path, root, is_dir).is_ignore()
+    }
+
+    /// Returns the by-ignore report class name of the file at `path`.
+    #[inline(always)]
+    pub fn classify(&self, path: &Path) -> &'static str {
+        if self.is_ignored(path, false) {
+            IGNORED_CLASS
+        } else {
+            TRACKED_CLASS
+        }
+    }
+
+    /// Returns `true` when the directory `dir` of the walk is ignored.  The
+    /// classifications are cached.
+    fn is_dir_ignored(&self, dir: &Path) -> bool {
+        match self.root_of(dir) {
+            Some((root, _)) if dir != root => {}
+            _ => return false,
+        }
+        if let Some(ignored) = self
+            .ignored_dirs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(dir)
+        {
+            return *ignored;
+        }
+        // the lock is not held here because the classification
+        // recurses into the parent directories
+        let ignored = self.is_ignored(dir, true);
+        self.ignored_dirs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(dir.to_path_buf(), ignored);
+        ignored
+    }
+
+    /// Returns the walk root path and absolute root path of `path`, or `None` when
+    /// `path` is not below a walk root path.  The deepest matching root is returned
+    /// when the root paths are nested.
+    fn root_of(&self, path: &Path) -> Option<&(PathBuf, PathBuf)> {
+        self.roots
+            .iter()
+            .filter(|(root, _)| path.starts_with(root))
+            .max_by_key(|(root, _)| root.components().count())
+    }
+
+    /// Returns the ignore file rule match of the `path` itself below the walk `root`.
+    fn matched(&self, path: &Path, root: Option<&(PathBuf, PathBuf)>, is_dir: bool) -> Match<()> {
+        let absolute_path = match root {
+            Some((root, absolute_root)) => match path.strip_prefix(root) {
+                Ok(relative) => absolute_root.join(relative),
+                Err(_) => path.to_path_buf(),
+            },
+            None => path.to_path_buf(),
+        };
+        let (mut m_ignore, mut m_gi) = (Match::None, Match::None);
+        let mut saw_git = false;
+        for dir in absolute_path.ancestors().skip(1) {
+            let matchers = self.dir_matchers(dir);
+            if m_ignore.is_none() {
+                if let Some(ignore) = &matchers.ignore {
+                    m_ignore = ignore.matched(&absolute_path, is_dir).map(|_| ());
+                }
+            }
+            if !saw_git && m_gi.is_none() {
+                if let Some(gitignore) = &matchers.gitignore {
+                    m_gi = gitignore.matched(&absolute_path, is_dir).map(|_| ());
+                }
+            }
+            saw_git = saw_git || matchers.has_git;
+        }
+        // .gitignore files are only respected in git repositories
+        if !saw_git {
+            m_gi = Match::None;
+        }
+        let mut m_explicit = Match::None;
+        for explicit in &self.explicit {
+            if m_explicit.is_none() {
+                m_explicit = explicit.matched(path, is_dir).map(|_| ());
+            }
+        }
+        m_ignore.or(m_gi).or(m_explicit)
+    }
+
+    /// Returns the cached ignore file matchers of the absolute directory path `dir`.
+    fn dir_matchers(&self, dir: &Path) -> Arc<DirMatchers> {
+        if let Some(matchers) = self
+            .dirs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(dir)
+        {
+            return Arc::clone(matchers);
+        }
+        let matchers = Arc::new(DirMatchers {
+            ignore: build_dir_ignore(dir, ".ignore"),
+            gitignore: if self.git_ignore {
+                build_dir_ignore(dir, ".gitignore")
+            } else {
+                None
+            },
+            has_git: self.git_ignore && dir.join(".git").exists(),
+        });
+        self.dirs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(dir.to_path_buf(), Arc::clone(&matchers));
+        matchers
+    }
+}
+
+/// Returns the matcher of the ignore file `file_name` in the directory `dir`, or `None`
+/// when the file is not present.  Invalid glob patterns in the ignore file are skipped,
+/// as they are in the file system walk.
+fn build_dir_ignore(dir: &Path, file_name: &str) -> Option<Gitignore> {
+    let path = dir.join(file_name);
+    if !path.is_file() {
+        return None;
+    }
+    let mut builder = GitignoreBuilder::new(dir);
+    builder.add(path);
+    builder
+        .build()
+        .ok()
+        .filter(|gitignore| !gitignore.is_empty())
+}
+
+/// Returns the matcher of an ignore-file option file at `path`.  The patterns match paths
+/// as they are walked, relative to the current working directory.
+fn build_explicit_ignore(path: &Path) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new("");
+    if let Some(err) = builder.add(path) {
+        return Err(anyhow::Error::new(err)).context("unable to read ignore file");
+    }
+    Ok(builder.build()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::walk::FileWalker;
+    use clap::Parser;
+    use std::collections::HashSet;
+    use tempfile::TempDir;
+
+    fn write_file(path: PathBuf, contents: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_ignore_classifier_git_repository() -> Result<()> {
+        let td = TempDir::new()?;
+        let root = td.path().join("repo");
+        std::fs::create_dir_all(root.join(".git"))?;
+        write_file(root.join(".gitignore"), "target/\n*.log\n");
+        write_file(root.join("a/.gitignore"), "!keep.log\n");
+        write_file(root.join("a/.ignore"), "local.txt\n");
+        write_file(root.join("a/keep.log"), "1");
+        write_file(root.join("a/local.txt"), "1");
+        write_file(root.join("a/main.rs"), "1");
+        write_file(root.join("target/debug/out"), "1");
+        write_file(root.join("x.log"), "1");
+        // .gitignore files above the repository root are not respected
+        write_file(td.path().join(".gitignore"), "*.rs\n");
+
+        let args = Args::parse_from(vec!["siz", root.to_str().unwrap()]);
+        let classifier = IgnoreClassifier::new(&args)?;
+
+        assert!(!classifier.is_ignored(&root, true));
+        assert!(classifier.is_ignored(&root.join("target"), true));
+        assert!(classifier.is_ignored(&root.join("target/debug/out"), false));
+        assert!(classifier.is_ignored(&root.join("x.log"), false));
+        assert!(!classifier.is_ignored(&root.join("a/keep.log"), false));
+        assert!(classifier.is_ignored(&root.join("a/local.txt"), false));
+        assert!(!classifier.is_ignored(&root.join("a/main.rs"), false));
+        assert_eq!(classifier.classify(&root.join("x.log")), IGNORED_CLASS);
+        assert_eq!(classifier.classify(&root.join("a/main.rs")), TRACKED_CLASS);
+
+        let args = Args::parse_from(vec!["siz", "--no-ignore-vcs", root.to_str().unwrap()]);
+        let classifier = IgnoreClassifier::new(&args)?;
+
+        assert!(!classifier.is_ignored(&root.join("target/debug/out"), false));
+        assert!(classifier.is_ignored(&root.join("a/local.txt"), false));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ignore_classifier_nested_repositories() -> Result<()> {
+        let td = TempDir::new()?;
+        let root = td.path().join("outer");
+        std::fs::create_dir_all(root.join(".git"))?;
+        std::fs::create_dir_all(root.join("inner/.git"))?;
+        write_file(root.join(".gitignore"), "*.log\nbuild/\n");
+        write_file(root.join("inner/.gitignore"), "*.tmp\n!*.log\n");
+        write_file(root.join("inner/.ignore"), "cache/\n");
+        write_file(root.join("a.log"), "1");
+        write_file(root.join("a.tmp"), "1");
+        write_file(root.join("build/out"), "1");
+        write_file(root.join("inner/b.log"), "1");
+        write_file(root.join("inner/b.tmp"), "1");
+        write_file(root.join("inner/cache/c"), "1");
+        write_file(root.join("inner/build/out"), "1");
+        write_file(root.join("inner/src/main.rs"), "1");
+
+        // the classification matches the files of the default walk
+        let root_path = root.to_str().unwrap();
+        let classifier = IgnoreClassifier::new(&Args::parse_from(vec!["siz", root_path]))?;
+        let walked: HashSet<PathBuf> =
+            FileWalker::new(&Args::parse_from(vec!["siz", "--hidden", root_path]))?
+                .map(|entry| entry.unwrap().into_path())
+                .collect();
+        let all_files = FileWalker::new(&Args::parse_from(vec![
+            "siz",
+            "--hidden",
+            "--no-ignore",
+            root_path,
+        ]))?;
+        for entry in all_files {
+            let path = entry?.into_path();
+            if path.components().any(|c| c.as_os_str() == ".git") {
+                continue;
+            }
+            assert_eq!(
+                classifier.is_ignored(&path, false),
+                !walked.contains(&path),
+                "{}",
+                path.display()
+            );
+        }
+        assert!(classifier.is_ignored(&root.join("a.log"), false));
+        assert!(!classifier.is_ignored(&root.join("inner/src/main.rs"), false));
+        assert!(classifier.is_ignored(&root.join("inner/b.tmp"), false));
+        assert!(classifier.is_ignored(&root.join("inner/cache/c"), false));
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_ignore_classifier_unreadable_directory() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let td = TempDir::new()?;
+        let root = td.path().join("repo");
+        std::fs::create_dir_all(root.join(".git"))?;
+        write_file(root.join(".gitignore"), "*.log\n");
+        write_file(root.join("locked/data.txt"), "1");
+        write_file(root.join("locked/debug.log"), "1");
+        let locked = root.join("locked");
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o000))?;
+
+        // the paths are classified with the ignore file rules whether or not
+        // the directory can be read
+        let args = Args::parse_from(vec!["siz", root.to_str().unwrap()]);
+        let classifier = IgnoreClassifier::new(&args)?;
+        let tracked = !classifier.is_ignored(&locked, true)
+            && !classifier.is_ignored(&locked.join("data.txt"), false)
+            && classifier.is_ignored(&locked.join("debug.log"), false);
+
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755))?;
+        assert!(tracked);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ignore_classifier_outside_git_repository() -> Result<()> {
+        let td = TempDir::new()?;
+        let root = td.path().join("dir");
+        write_file(root.join(".gitignore"), "*.log\n");
+        write_file(root.join(".ignore"), "*.tmp\n");
+        write_file(td.path().join("custom-ignore"), "*.bak\n");
+
+        let custom_ignore = td.path().join("custom-ignore");
+        let args = Args::parse_from(vec![
+            "siz",
+            "--ignore-file",
+            custom_ignore.to_str().unwrap(),
+            root.to_str().unwrap(),
+        ]);
+        let classifier = IgnoreClassifier::new(&args)?;
+
+        assert!(!classifier.is_ignored(&root.join("a.log"), false));
+        assert!(classifier.is_ignored(&root.join("a.tmp"), false));
+        assert!(classifier.is_ignored(&root.join("a.bak"), false));
+
+        let args = Args::parse_from(vec![
+            "siz",
+            "--ignore-file",
+            "missing",
+            root.to_str().unwrap(),
+        ]);
+        assert!(IgnoreClassifier::new(&args).is_err());
+        Ok(())
+    }
+}
@@ -2,10 +2,23 @@
 //! This crate provides the core functionality for the executable.  It is not
 //! designed for use as a third party library.
 
+pub mod aggregate;
 pub mod args;
+pub mod budget;
+pub mod config;
+pub mod duplicates;
+pub mod exclude;
+pub mod filelist;
+pub mod filter;
 pub mod format;
 pub mod fuzzy;
+pub mod ignored;
+pub mod links;
+pub mod record;
+pub mod select;
+pub mod snapshot;
 pub mod stdstreams;
+pub mod summary;
 pub mod types;
 pub mod types_default;
 pub mod walk;
@@ -0,0 +1,101 @@
+//! This module provides support for hard link detection.
+use std::collections::HashSet;
+use std::fs::Metadata;
+use std::sync::Mutex;
+
+/// `HardLinkTracker` is a struct that detects files that were already seen during a
+/// file system walk through another hard link or a followed symbolic link.
+///
+/// Files are identified by their (device, inode) pair.  Only files with more than one
+/// hard link are tracked by default, so the memory use scales with the number of hard
+/// linked files rather than with the number of files in the walk.  The tracker is
+/// thread-safe and is shared across the threads of a parallel walk.
+///
+/// Hard link detection is supported on Unix platforms.  On other platforms, files are
+/// never reported as duplicates.
+///
+/// # Examples
+///
+/// ```
+/// use siz::links::HardLinkTracker;
+///
+/// let tracker = HardLinkTracker::new(true);
+/// let metadata = std::fs::metadata("Cargo.toml").unwrap();
+/// assert!(!tracker.is_duplicate(&metadata));
+/// # #[cfg(unix)]
+/// assert!(tracker.is_duplicate(&metadata));
+/// ```
+#[derive(Debug, Default)]
+pub struct HardLinkTracker {
+    seen: Mutex<HashSet<(u64, u64)>>,
+    track_all_files: bool,
+}
+
+impl HardLinkTracker {
+    /// Creates a new, empty `HardLinkTracker`.
+    ///
+    /// # Parameters
+    ///
+    /// * `track_all_files`: A boolean that indicates whether to track files with a single
+    ///   hard link.  This is required to detect files that are reached through followed
+    ///   symbolic links.
+    pub fn new(track_all_files: bool) -> Self {
+        Self {
+            seen: Mutex::new(HashSet::new()),
+            track_all_files,
+        }
+    }
+
+    /// Returns `true` if the file with `metadata` was already seen by the tracker, and
+    /// records the file as seen otherwise.
+    #[inline(always)]
+    pub fn is_duplicate(&self, metadata: &Metadata) -> bool {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+
+            if !self.track_all_files && metadata.nlink() <= 1 {
+                return false;
+            }
+            match self.seen.lock() {
+                Ok(mut seen) => !seen.insert((metadata.dev(), metadata.ino())),
+                Err(_) => false,
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = (metadata, &self.seen, self.track_all_files);
+            false
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_hard_link_tracker() -> std::io::Result<()> {
+        let td = TempDir::new()?;
+        let original = td.path().join("original.txt");
+        let link = td.path().join("link.txt");
+        let single = td.path().join("single.txt");
+        std::fs::write(&original, "siz")?;
+        std::fs::write(&single, "siz")?;
+        std::fs::hard_link(&original, &link)?;
+
+        let tracker = HardLinkTracker::new(false);
+        assert!(!tracker.is_duplicate(&std::fs::metadata(&original)?));
+        assert!(tracker.is_duplicate(&std::fs::metadata(&link)?));
+        assert!(tracker.is_duplicate(&std::fs::metadata(&original)?));
+        // files with a single link are not tracked by default
+        assert!(!tracker.is_duplicate(&std::fs::metadata(&single)?));
+        assert!(!tracker.is_duplicate(&std::fs::metadata(&single)?));
+
+        let tracker = HardLinkTracker::new(true);
+        assert!(!tracker.is_duplicate(&std::fs::metadata(&single)?));
+        assert!(tracker.is_duplicate(&std::fs::metadata(&single)?));
+        Ok(())
+    }
+}
@@ -0,0 +1,348 @@
+//! This module provides the file record data structure that is used in size reports.
+use std::fs::Metadata;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::args::{Args, LinkCount};
+use crate::links::HardLinkTracker;
+
+/// Returns the allocated disk usage of a file in bytes.
+///
+/// On Unix platforms, this is the number of allocated 512 byte blocks (`st_blocks * 512`),
+/// which can be smaller than the apparent size for sparse files and larger than the
+/// apparent size for small files on file systems with large blocks.  On other platforms,
+/// the allocated disk usage is not available and the apparent size is returned.
+#[inline(always)]
+pub fn allocated_size(metadata: &Metadata) -> u64 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        metadata.blocks().saturating_mul(512)
+    }
+    #[cfg(not(unix))]
+    {
+        metadata.len()
+    }
+}
+
+/// Returns the ID of the device that contains the file with `metadata`.
+///
+/// Returns `None` on platforms other than Unix, where device IDs are not available.
+#[inline(always)]
+pub fn device_id(metadata: &Metadata) -> Option<u64> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some(metadata.dev())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        None
+    }
+}
+
+/// Returns the inode number of the file with `metadata`.
+///
+/// Returns `None` on platforms other than Unix, where inode numbers are not available.
+#[inline(always)]
+pub fn inode_id(metadata: &Metadata) -> Option<u64> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some(metadata.ino())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        None
+    }
+}
+
+/// `SizeMode` is an enum that represents the file size that is used as the report size.
+///
+/// # Variants
+///
+/// * `Apparent`: The apparent file size, the length of the file contents in bytes.
+/// * `Allocated`: The allocated disk usage of the file in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SizeMode {
+    #[default]
+    Apparent,
+    Allocated,
+}
+
+impl SizeMode {
+    /// Returns the `SizeMode` that is requested on the command line.
+    pub fn from_args(args: &Args) -> Self {
+        if args.disk_usage {
+            SizeMode::Allocated
+        } else {
+            SizeMode::Apparent
+        }
+    }
+
+    /// Returns the size of the file with `metadata` in bytes.
+    #[inline(always)]
+    pub fn size(&self, metadata: &Metadata) -> u64 {
+        match self {
+            SizeMode::Apparent => metadata.len(),
+            SizeMode::Allocated => allocated_size(metadata),
+        }
+    }
+}
+
+/// `FileRecord` is a struct that represents a single size report entry.
+///
+/// # Fields
+///
+/// * `size`: The report size of the file (or the cumulative size of a directory) in bytes.
+///   This is the apparent size by default, and the allocated disk usage in disk usage mode.
+/// * `path`: The file or directory path.
+/// * `modified`: The optional last modification time of the path.
+/// * `readonly`: The optional read-only permission state of the path.
+/// * `allocated`: The optional allocated disk usage of the file in bytes.
+/// * `apparent`: The optional apparent size of the file in bytes.
+/// * `duplicate_link`: An optional boolean that indicates whether the file is a duplicate
+///   hard link to a previously reported file.  This is `None` when hard links are not tracked.
+/// * `device`: The optional ID of the device that contains the file.
+/// * `inode`: The optional inode number of the file.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+///
+/// use siz::record::FileRecord;
+///
+/// let metadata = std::fs::metadata("Cargo.toml").unwrap();
+/// let record = FileRecord::new(PathBuf::from("Cargo.toml"), &metadata);
+/// assert_eq!(record.size, metadata.len());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileRecord {
+    pub size: u64,
+    pub path: PathBuf,
+    pub modified: Option<SystemTime>,
+    pub readonly: Option<bool>,
+    pub allocated: Option<u64>,
+    pub apparent: Option<u64>,
+    pub duplicate_link: Option<bool>,
+    pub device: Option<u64>,
+    pub inode: Option<u64>,
+}
+
+impl FileRecord {
+    /// Creates a new `FileRecord` from a path and the file system metadata for the path.
+    ///
+    /// The report size is the apparent size of the file.
+    #[inline(always)]
+    pub fn new(path: PathBuf, metadata: &Metadata) -> Self {
+        Self {
+            size: metadata.len(),
+            path,
+            modified: metadata.modified().ok(),
+            readonly: Some(metadata.permissions().readonly()),
+            allocated: Some(allocated_size(metadata)),
+            apparent: Some(metadata.len()),
+            duplicate_link: None,
+            device: device_id(metadata),
+            inode: inode_id(metadata),
+        }
+    }
+
+    /// Creates a new `FileRecord` from a path and the file system metadata for the path
+    /// with the report size that is defined by `size_mode`.
+    #[inline(always)]
+    pub fn with_size_mode(path: PathBuf, metadata: &Metadata, size_mode: SizeMode) -> Self {
+        Self {
+            size: size_mode.size(metadata),
+            ..Self::new(path, metadata)
+        }
+    }
+
+    /// Creates a new `FileRecord` from a size and a path without file system metadata.
+    ///
+    /// This is used for report entries with calculated sizes, like cumulative directory
+    /// size totals.
+    #[inline(always)]
+    pub fn from_size(size: u64, path: PathBuf) -> Self {
+        Self {
+            size,
+            path,
+            modified: None,
+            readonly: None,
+            allocated: None,
+            apparent: None,
+            duplicate_link: None,
+            device: None,
+            inode: None,
+        }
+    }
+
+    /// Returns the last modification time of the path in seconds since the Unix epoch.
+    ///
+    /// Returns `None` when the modification time is not available or predates the epoch.
+    #[inline(always)]
+    pub fn modified_unix_seconds(&self) -> Option<u64> {
+        self.modified
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_secs())
+    }
+}
+
+/// `RecordBuilder` is a struct that creates the `FileRecord` instances of a file system walk
+/// with the report size and hard link handling that are requested on the command line.
+///
+/// A `RecordBuilder` is shared across the threads of a parallel walk.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+///
+/// use clap::Parser;
+///
+/// use siz::args::Args;
+/// use siz::record::RecordBuilder;
+///
+/// let args = Args::parse_from(vec!["siz", "--count-links", "once", "."]);
+/// let record_builder = RecordBuilder::from_args(&args);
+/// let metadata = std::fs::metadata("Cargo.toml").unwrap();
+/// let record = record_builder.build(PathBuf::from("Cargo.toml"), &metadata);
+/// assert_eq!(record.unwrap().size, metadata.len());
+/// ```
+#[derive(Debug)]
+pub struct RecordBuilder {
+    size_mode: SizeMode,
+    hard_links: Option<(LinkCount, HardLinkTracker)>,
+}
+
+impl RecordBuilder {
+    /// Constructs a new `RecordBuilder` from the command line arguments.
+    pub fn from_args(args: &Args) -> Self {
+        Self {
+            size_mode: SizeMode::from_args(args),
+            // followed symbolic links can reach files that have a single hard link
+            hard_links: args
+                .count_links
+                .map(|link_count| (link_count, HardLinkTracker::new(args.follow))),
+        }
+    }
+
+    /// Returns a new `FileRecord` for the file at `path` with `metadata`, or `None` when
+    /// the file is a duplicate hard link that is suppressed in the report.
+    #[inline(always)]
+    pub fn build(&self, path: PathBuf, metadata: &Metadata) -> Option<FileRecord> {
+        let mut record = FileRecord::with_size_mode(path, metadata, self.size_mode);
+        if let Some((link_count, tracker)) = &self.hard_links {
+            let duplicate_link = tracker.is_duplicate(metadata);
+            if duplicate_link && *link_count == LinkCount::Once {
+                return None;
+            }
+            record.duplicate_link = Some(duplicate_link);
+        }
+        Some(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_file_record_from_size() {
+        let record = FileRecord::from_size(10, PathBuf::from("a"));
+        assert_eq!(record.size, 10);
+        assert_eq!(record.path, PathBuf::from("a"));
+        assert_eq!(record.modified, None);
+        assert_eq!(record.readonly, None);
+        assert_eq!(record.allocated, None);
+        assert_eq!(record.apparent, None);
+        assert_eq!(record.duplicate_link, None);
+        assert_eq!(record.device, None);
+        assert_eq!(record.inode, None);
+        assert_eq!(record.modified_unix_seconds(), None);
+    }
+
+    #[test]
+    fn test_file_record_with_size_mode() {
+        use clap::Parser;
+
+        let metadata = std::fs::metadata("Cargo.toml").unwrap();
+        let args = Args::parse_from(vec!["siz", "."]);
+        let size_mode = SizeMode::from_args(&args);
+        assert_eq!(size_mode, SizeMode::Apparent);
+        let record = FileRecord::with_size_mode(PathBuf::from("Cargo.toml"), &metadata, size_mode);
+        assert_eq!(record.size, metadata.len());
+        assert_eq!(record.allocated, Some(allocated_size(&metadata)));
+
+        let args = Args::parse_from(vec!["siz", "--disk-usage", "."]);
+        let size_mode = SizeMode::from_args(&args);
+        assert_eq!(size_mode, SizeMode::Allocated);
+        let record = FileRecord::with_size_mode(PathBuf::from("Cargo.toml"), &metadata, size_mode);
+        assert_eq!(record.size, allocated_size(&metadata));
+        assert_eq!(record.apparent, Some(metadata.len()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_record_builder_count_links() -> std::io::Result<()> {
+        use clap::Parser;
+
+        let td = tempfile::TempDir::new()?;
+        let original = td.path().join("original.txt");
+        let link = td.path().join("link.txt");
+        std::fs::write(&original, "siz")?;
+        std::fs::hard_link(&original, &link)?;
+        let build_all = |record_builder: &RecordBuilder| -> std::io::Result<Vec<Option<bool>>> {
+            let mut duplicate_links = Vec::new();
+            for path in [&original, &link] {
+                let metadata = std::fs::metadata(path)?;
+                if let Some(record) = record_builder.build(path.clone(), &metadata) {
+                    duplicate_links.push(record.duplicate_link);
+                }
+            }
+            Ok(duplicate_links)
+        };
+
+        let args = Args::parse_from(vec!["siz", "."]);
+        assert_eq!(
+            build_all(&RecordBuilder::from_args(&args))?,
+            vec![None, None]
+        );
+        let args = Args::parse_from(vec!["siz", "--count-links", "all", "."]);
+        assert_eq!(
+            build_all(&RecordBuilder::from_args(&args))?,
+            vec![Some(false), Some(true)]
+        );
+        let args = Args::parse_from(vec!["siz", "--count-links", "once", "."]);
+        assert_eq!(
+            build_all(&RecordBuilder::from_args(&args))?,
+            vec![Some(false)]
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_allocated_size_sparse_file() -> std::io::Result<()> {
+        let td = tempfile::TempDir::new()?;
+        let path = td.path().join("sparse.img");
+        let file = std::fs::File::create(&path)?;
+        // extend the file without writing data blocks
+        file.set_len(64 * 1024 * 1024)?;
+        let metadata = std::fs::metadata(&path)?;
+        assert_eq!(metadata.len(), 64 * 1024 * 1024);
+        assert!(allocated_size(&metadata) < metadata.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_record_modified_unix_seconds() {
+        let mut record = FileRecord::from_size(10, PathBuf::from("a"));
+        record.modified = Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+        assert_eq!(record.modified_unix_seconds(), Some(1_700_000_000));
+    }
+}
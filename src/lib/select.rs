@@ -0,0 +1,251 @@
+//! This module provides support for bounded largest and smallest size selections.
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+use crate::args::Args;
+use crate::record::FileRecord;
+
+/// `SizeSelection` is an enum that represents a bounded selection of report records.
+///
+/// # Variants
+///
+/// * `Largest`: Select the N largest records.
+/// * `Smallest`: Select the N smallest records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeSelection {
+    Largest(usize),
+    Smallest(usize),
+}
+
+impl SizeSelection {
+    /// Returns the `SizeSelection` that is requested on the command line, or `None`
+    /// when the report is not limited.
+    pub fn from_args(args: &Args) -> Option<Self> {
+        match (args.top, args.bottom) {
+            (Some(n), _) => Some(SizeSelection::Largest(n)),
+            (None, Some(n)) => Some(SizeSelection::Smallest(n)),
+            (None, None) => None,
+        }
+    }
+}
+
+/// A `FileRecord` wrapper that is ordered by (size, path).
+#[derive(Debug, PartialEq, Eq)]
+struct SizeOrderedRecord(FileRecord);
+
+impl Ord for SizeOrderedRecord {
+    #[inline(always)]
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.0.size, &self.0.path).cmp(&(other.0.size, &other.0.path))
+    }
+}
+
+impl PartialOrd for SizeOrderedRecord {
+    #[inline(always)]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// `RecordHeap` is a bounded heap that keeps the N largest or N smallest report records.
+///
+/// Memory use and the cost of each insertion scale with N rather than with the number
+/// of records that are pushed onto the heap.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+///
+/// use siz::record::FileRecord;
+/// use siz::select::{RecordHeap, SizeSelection};
+///
+/// let mut heap = RecordHeap::new(SizeSelection::Largest(2));
+/// for size in [5, 1, 20, 10] {
+///     heap.push(FileRecord::from_size(size, PathBuf::from(size.to_string())));
+/// }
+/// let sizes: Vec<u64> = heap.into_sorted_vec().iter().map(|r| r.size).collect();
+/// assert_eq!(sizes, vec![10, 20]);
+/// ```
+#[derive(Debug)]
+pub struct RecordHeap {
+    capacity: usize,
+    heap: SelectionHeap,
+}
+
+#[derive(Debug)]
+enum SelectionHeap {
+    /// A min-heap of the largest records, the smallest retained record is on top.
+    Largest(BinaryHeap<Reverse<SizeOrderedRecord>>),
+    /// A max-heap of the smallest records, the largest retained record is on top.
+    Smallest(BinaryHeap<SizeOrderedRecord>),
+}
+
+impl RecordHeap {
+    /// Creates a new, empty `RecordHeap` for the `selection`.
+    pub fn new(selection: SizeSelection) -> Self {
+        match selection {
+            SizeSelection::Largest(n) => Self {
+                capacity: n,
+                heap: SelectionHeap::Largest(BinaryHeap::new()),
+            },
+            SizeSelection::Smallest(n) => Self {
+                capacity: n,
+                heap: SelectionHeap::Smallest(BinaryHeap::new()),
+            },
+        }
+    }
+
+    /// Returns the `SizeSelection` of the heap.
+    pub fn selection(&self) -> SizeSelection {
+        match self.heap {
+            SelectionHeap::Largest(_) => SizeSelection::Largest(self.capacity),
+            SelectionHeap::Smallest(_) => SizeSelection::Smallest(self.capacity),
+        }
+    }
+
+    /// Pushes a record onto the heap.  The record is discarded when the heap is full
+    /// and the record is not retained in the selection.
+    #[inline(always)]
+    pub fn push(&mut self, record: FileRecord) {
+        let record = SizeOrderedRecord(record);
+        match &mut self.heap {
+            SelectionHeap::Largest(heap) => {
+                if heap.len() < self.capacity {
+                    heap.push(Reverse(record));
+                } else if let Some(Reverse(min)) = heap.peek() {
+                    if record > *min {
+                        heap.pop();
+                        heap.push(Reverse(record));
+                    }
+                }
+            }
+            SelectionHeap::Smallest(heap) => {
+                if heap.len() < self.capacity {
+                    heap.push(record);
+                } else if let Some(max) = heap.peek() {
+                    if record < *max {
+                        heap.pop();
+                        heap.push(record);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Pushes all of the records of another `RecordHeap` onto this heap.
+    pub fn merge(&mut self, other: RecordHeap) {
+        for record in other.into_sorted_vec() {
+            self.push(record);
+        }
+    }
+
+    /// Consumes the `RecordHeap` and returns the selected records in ascending
+    /// (size, path) order.
+    pub fn into_sorted_vec(self) -> Vec<FileRecord> {
+        match self.heap {
+            SelectionHeap::Largest(heap) => heap
+                .into_sorted_vec()
+                .into_iter()
+                .rev()
+                .map(|Reverse(record)| record.0)
+                .collect(),
+            SelectionHeap::Smallest(heap) => heap
+                .into_sorted_vec()
+                .into_iter()
+                .map(|record| record.0)
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn push_all(heap: &mut RecordHeap, records: &[(u64, &str)]) {
+        for (size, path) in records {
+            heap.push(FileRecord::from_size(*size, PathBuf::from(path)));
+        }
+    }
+
+    fn sizes_paths(records: Vec<FileRecord>) -> Vec<(u64, String)> {
+        records
+            .into_iter()
+            .map(|r| (r.size, r.path.to_string_lossy().into_owned()))
+            .collect()
+    }
+
+    const RECORDS: [(u64, &str); 7] = [
+        (5, "e"),
+        (1, "a"),
+        (20, "f"),
+        (10, "c"),
+        (10, "b"),
+        (0, "z"),
+        (7, "d"),
+    ];
+
+    #[test]
+    fn test_record_heap_largest() {
+        let mut heap = RecordHeap::new(SizeSelection::Largest(3));
+        push_all(&mut heap, &RECORDS);
+        assert_eq!(
+            sizes_paths(heap.into_sorted_vec()),
+            vec![(10, "b".into()), (10, "c".into()), (20, "f".into())]
+        );
+    }
+
+    #[test]
+    fn test_record_heap_smallest() {
+        let mut heap = RecordHeap::new(SizeSelection::Smallest(3));
+        push_all(&mut heap, &RECORDS);
+        assert_eq!(
+            sizes_paths(heap.into_sorted_vec()),
+            vec![(0, "z".into()), (1, "a".into()), (5, "e".into())]
+        );
+    }
+
+    #[test]
+    fn test_record_heap_capacity_larger_than_input() {
+        let mut heap = RecordHeap::new(SizeSelection::Largest(100));
+        push_all(&mut heap, &RECORDS);
+        assert_eq!(heap.into_sorted_vec().len(), RECORDS.len());
+
+        let mut heap = RecordHeap::new(SizeSelection::Smallest(0));
+        push_all(&mut heap, &RECORDS);
+        assert!(heap.into_sorted_vec().is_empty());
+    }
+
+    #[test]
+    fn test_record_heap_merge() {
+        let mut heap = RecordHeap::new(SizeSelection::Largest(2));
+        push_all(&mut heap, &RECORDS[..3]);
+        let mut other = RecordHeap::new(heap.selection());
+        push_all(&mut other, &RECORDS[3..]);
+        heap.merge(other);
+        assert_eq!(
+            sizes_paths(heap.into_sorted_vec()),
+            vec![(10, "c".into()), (20, "f".into())]
+        );
+    }
+
+    #[test]
+    fn test_size_selection_from_args() {
+        let args = Args::parse_from(vec!["siz", "--top", "5", "."]);
+        assert_eq!(
+            SizeSelection::from_args(&args),
+            Some(SizeSelection::Largest(5))
+        );
+        let args = Args::parse_from(vec!["siz", "--bottom", "3", "."]);
+        assert_eq!(
+            SizeSelection::from_args(&args),
+            Some(SizeSelection::Smallest(3))
+        );
+        let args = Args::parse_from(vec!["siz", "."]);
+        assert_eq!(SizeSelection::from_args(&args), None);
+    }
+}
@@ -0,0 +1,343 @@
+//! This module provides support for saving file size reports to snapshot files and
+//! for reporting the file changes between two snapshots.
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::record::FileRecord;
+
+/// The snapshot file format version.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// `SnapshotEntry` is a struct that represents a file in a snapshot.
+///
+/// # Fields
+///
+/// * `path`: The file path, relative to the snapshot root.
+/// * `size`: The file size in bytes.
+/// * `modified`: The last modification time in seconds since the Unix epoch, if available.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: Option<u64>,
+}
+
+/// `Snapshot` is a struct that represents the files of a file size report.
+///
+/// The file paths are stored relative to the snapshot root so that snapshots of
+/// a directory tree in different locations (e.g., two build output directories)
+/// can be compared.  Snapshots are saved as JSON files.
+///
+/// # Fields
+///
+/// * `version`: The snapshot file format version.
+/// * `root`: The file or directory path of the report.
+/// * `files`: The `SnapshotEntry` instances of the report files.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::{Path, PathBuf};
+///
+/// use siz::record::FileRecord;
+/// use siz::snapshot::Snapshot;
+///
+/// let mut snapshot = Snapshot::new(Path::new("build"));
+/// snapshot.add(&FileRecord::from_size(1024, PathBuf::from("build/app.js")));
+/// assert_eq!(snapshot.files[0].path, PathBuf::from("app.js"));
+/// assert_eq!(snapshot.files[0].size, 1024);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub root: PathBuf,
+    pub files: Vec<SnapshotEntry>,
+}
+
+impl Snapshot {
+    /// Creates a new, empty `Snapshot` of the report at `root`.
+    pub fn new(root: &Path) -> Self {
+        Self {
+            version: SNAPSHOT_VERSION,
+            root: root.to_path_buf(),
+            files: Vec::new(),
+        }
+    }
+
+    /// Adds a report file to the snapshot.
+    ///
+    /// The record path is stored relative to the snapshot root.  A record at the root
+    /// path (i.e., a report of a single file) is stored with its file name.
+    pub fn add(&mut self, record: &FileRecord) {
+        let path = match record.path.strip_prefix(&self.root) {
+            Ok(relative) if !relative.as_os_str().is_empty() => relative,
+            _ => record
+                .path
+                .file_name()
+                .map(Path::new)
+                .unwrap_or(&record.path),
+        };
+        self.files.push(SnapshotEntry {
+            path: path.to_path_buf(),
+            size: record.size,
+            modified: record.modified_unix_seconds(),
+        });
+    }
+
+    /// Saves the snapshot as a JSON file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the file cannot be written, or when the snapshot root or a
+    /// snapshot file path is not valid UTF-8.  JSON snapshot files store UTF-8 paths, and
+    /// a lossy conversion could map different paths to the same snapshot path.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut paths =
+            std::iter::once(&self.root).chain(self.files.iter().map(|entry| &entry.path));
+        if let Some(invalid) = paths.find(|path| path.to_str().is_none()) {
+            anyhow::bail!(
+                "unable to write snapshot file {}: path is not valid UTF-8: {}",
+                path.display(),
+                invalid.display()
+            );
+        }
+        let file = File::create(path)
+            .with_context(|| format!("unable to write snapshot file: {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, self)?;
+        writeln!(writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Loads a snapshot from the JSON file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the file cannot be read, is not a snapshot file, or
+    /// has an unsupported snapshot file format version.
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("unable to read snapshot file: {}", path.display()))?;
+        let snapshot: Snapshot = serde_json::from_reader(BufReader::new(file))
+            .with_context(|| format!("invalid snapshot file: {}", path.display()))?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            anyhow::bail!(
+                "unsupported snapshot file version {} in {} (expected version {})",
+                snapshot.version,
+                path.display(),
+                SNAPSHOT_VERSION
+            );
+        }
+        Ok(snapshot)
+    }
+}
+
+/// `ChangeKind` is an enum that represents how a file changed between two snapshots.
+///
+/// # Variants
+///
+/// * `Added`: The file is in the new snapshot only.
+/// * `Removed`: The file is in the old snapshot only.
+/// * `Grown`: The file is larger in the new snapshot.
+/// * `Shrunk`: The file is smaller in the new snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Grown,
+    Shrunk,
+}
+
+impl ChangeKind {
+    /// Returns the report name of the change kind.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Grown => "grown",
+            ChangeKind::Shrunk => "shrunk",
+        }
+    }
+}
+
+/// `FileChange` is a struct that represents a file that changed between two snapshots.
+///
+/// # Fields
+///
+/// * `path`: The file path, relative to the snapshot roots.
+/// * `kind`: The `ChangeKind` of the change.
+/// * `old_size`: The file size in bytes in the old snapshot, if present.
+/// * `new_size`: The file size in bytes in the new snapshot, if present.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub kind: ChangeKind,
+    pub old_size: Option<u64>,
+    pub new_size: Option<u64>,
+}
+
+impl FileChange {
+    /// Returns the signed size change in bytes.
+    #[inline(always)]
+    pub fn delta(&self) -> i64 {
+        self.new_size.unwrap_or(0) as i64 - self.old_size.unwrap_or(0) as i64
+    }
+}
+
+/// Returns the files that were added, removed, grown, or shrunk between the `old`
+/// and the `new` snapshots, in path order.
+///
+/// Files with the same size in both snapshots are not reported.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::{Path, PathBuf};
+///
+/// use siz::record::FileRecord;
+/// use siz::snapshot::{diff_snapshots, ChangeKind, Snapshot};
+///
+/// let mut old = Snapshot::new(Path::new("build"));
+/// old.add(&FileRecord::from_size(100, PathBuf::from("build/app.js")));
+/// let mut new = Snapshot::new(Path::new("build"));
+/// new.add(&FileRecord::from_size(150, PathBuf::from("build/app.js")));
+///
+/// let changes = diff_snapshots(&old, &new);
+/// assert_eq!(changes[0].kind, ChangeKind::Grown);
+/// assert_eq!(changes[0].delta(), 50);
+/// ```
+pub fn diff_snapshots(old: &Snapshot, new: &Snapshot) -> Vec<FileChange> {
+    let mut sizes: BTreeMap<&Path, (Option<u64>, Option<u64>)> = BTreeMap::new();
+    for entry in &old.files {
+        sizes.entry(&entry.path).or_default().0 = Some(entry.size);
+    }
+    for entry in &new.files {
+        sizes.entry(&entry.path).or_default().1 = Some(entry.size);
+    }
+    sizes
+        .into_iter()
+        .filter_map(|(path, (old_size, new_size))| {
+            let kind = match (old_size, new_size) {
+                (None, Some(_)) => ChangeKind::Added,
+                (Some(_), None) => ChangeKind::Removed,
+                (Some(old), Some(new)) if new > old => ChangeKind::Grown,
+                (Some(old), Some(new)) if new < old => ChangeKind::Shrunk,
+                _ => return None,
+            };
+            Some(FileChange {
+                path: path.to_path_buf(),
+                kind,
+                old_size,
+                new_size,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn build_snapshot(files: &[(&str, u64)]) -> Snapshot {
+        let mut snapshot = Snapshot::new(Path::new("root"));
+        for (path, size) in files {
+            snapshot.add(&FileRecord::from_size(*size, Path::new("root").join(path)));
+        }
+        snapshot
+    }
+
+    #[test]
+    fn test_snapshot_add_relative_paths() {
+        let mut snapshot = Snapshot::new(Path::new("root/file.txt"));
+        snapshot.add(&FileRecord::from_size(1, PathBuf::from("root/file.txt")));
+        let mut snapshot_dir = Snapshot::new(Path::new("root"));
+        snapshot_dir.add(&FileRecord::from_size(
+            1,
+            PathBuf::from("root/dir/file.txt"),
+        ));
+
+        assert_eq!(snapshot.files[0].path, PathBuf::from("file.txt"));
+        assert_eq!(snapshot_dir.files[0].path, PathBuf::from("dir/file.txt"));
+    }
+
+    #[test]
+    fn test_snapshot_save_load() -> Result<()> {
+        let td = TempDir::new()?;
+        let snapshot_path = td.path().join("snapshot.json");
+        let snapshot = build_snapshot(&[("a.txt", 1), ("dir/b.txt", 2)]);
+        snapshot.save(&snapshot_path)?;
+
+        assert_eq!(Snapshot::load(&snapshot_path)?, snapshot);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_snapshot_non_utf8_paths() -> Result<()> {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let td = TempDir::new()?;
+        let mut snapshot = Snapshot::new(Path::new("root"));
+        for name in [b"f\xff.txt", b"f\xfe.txt"] {
+            let path = Path::new("root").join(OsStr::from_bytes(name));
+            snapshot.add(&FileRecord::from_size(1, path));
+        }
+
+        // different non-UTF-8 paths do not collide
+        assert_ne!(snapshot.files[0].path, snapshot.files[1].path);
+        assert!(snapshot
+            .save(&td.path().join("snapshot.json"))
+            .unwrap_err()
+            .to_string()
+            .contains("path is not valid UTF-8"));
+        assert!(!td.path().join("snapshot.json").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshot_load_invalid_files() -> Result<()> {
+        let td = TempDir::new()?;
+        let invalid_path = td.path().join("invalid.json");
+        std::fs::write(&invalid_path, "[1, 2, 3]")?;
+        let version_path = td.path().join("version.json");
+        std::fs::write(&version_path, r#"{"version":99,"root":"root","files":[]}"#)?;
+
+        assert!(Snapshot::load(&td.path().join("missing.json")).is_err());
+        assert!(Snapshot::load(&invalid_path).is_err());
+        assert!(Snapshot::load(&version_path)
+            .unwrap_err()
+            .to_string()
+            .contains("unsupported snapshot file version 99"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_snapshots() {
+        let old = build_snapshot(&[("removed", 10), ("grown", 5), ("shrunk", 7), ("same", 3)]);
+        let new = build_snapshot(&[("added", 4), ("grown", 15), ("shrunk", 2), ("same", 3)]);
+        let changes = diff_snapshots(&old, &new);
+
+        let summary: Vec<(&str, ChangeKind, i64)> = changes
+            .iter()
+            .map(|change| (change.path.to_str().unwrap(), change.kind, change.delta()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("added", ChangeKind::Added, 4),
+                ("grown", ChangeKind::Grown, 10),
+                ("removed", ChangeKind::Removed, -10),
+                ("shrunk", ChangeKind::Shrunk, -5),
+            ]
+        );
+    }
+}
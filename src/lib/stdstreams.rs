@@ -1,10 +1,22 @@
 //! This module provides functions for working with standard input and output streams.
 use std::io::Write;
 use std::path::{Path, MAIN_SEPARATOR_STR};
-use std::sync::OnceLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
 
-use crate::args::Args;
+use crate::aggregate::GroupTotal;
+use crate::args::{Args, Column, LinkCount, OutputFormat};
+use crate::budget::BudgetViolation;
+use crate::duplicates::DuplicateSet;
+use crate::format::{build_binary_size_formatter, build_metric_size_formatter, format_timestamp};
+use crate::record::FileRecord;
+use crate::snapshot::{ChangeKind, FileChange};
+use crate::summary::{LargestFile, Summary, SummaryBuilder};
+use crate::types::TypeClassifier;
+use anyhow::Result;
 use colored::*;
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
 
 /// A `std::sync::OnceLock` for the platform-specific colored separator
 /// string used to format file path output.
@@ -26,18 +38,80 @@ where
     T: std::fmt::Display,
     U: std::fmt::Display,
 {
-    writeln!(std::io::stdout(), "{}\t{}", filesize, filepath)?;
+    write_stdout_terminated(filesize, filepath, '\n')
+}
+
+/// Writes the given `filesize` and `filepath` to stdout, followed by the record
+/// `terminator`.
+///
+/// # Arguments
+///
+/// * `filesize` - The size of the file to be written.
+/// * `filepath` - The path of the file to be written.
+/// * `terminator` - The record terminator, a newline or a NUL character.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the write operation is successful, otherwise returns an `std::io::Error`.
+#[inline(always)]
+pub fn write_stdout_terminated<T, U>(
+    filesize: T,
+    filepath: U,
+    terminator: char,
+) -> Result<(), std::io::Error>
+where
+    T: std::fmt::Display,
+    U: std::fmt::Display,
+{
+    write!(
+        std::io::stdout(),
+        "{}\t{}{}",
+        filesize,
+        filepath,
+        terminator
+    )?;
+    Ok(())
+}
+
+/// Writes the given `filepath` to stdout.
+///
+/// The path is written with its raw bytes on Unix platforms, so that paths that are
+/// not valid UTF-8 can be passed to other commands, e.g., with `xargs -0`.
+///
+/// # Arguments
+///
+/// * `filepath` - The path of the file to be written.
+/// * `terminator` - The record terminator, a newline or a NUL character.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the write operation is successful, otherwise returns an `std::io::Error`.
+#[inline(always)]
+pub fn write_path_stdout(filepath: &Path, terminator: char) -> Result<(), std::io::Error> {
+    // hold the stdout lock so that the path and terminator are
+    // written together across the threads of a parallel walk
+    let mut stdout = std::io::stdout().lock();
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        stdout.write_all(filepath.as_os_str().as_bytes())?;
+    }
+    #[cfg(not(unix))]
+    write!(stdout, "{}", filepath.display())?;
+    write!(stdout, "{}", terminator)?;
     Ok(())
 }
 
 /// Formats and prints the file information to the standard output stream based
 /// on the provided arguments.
 ///
+/// Records are terminated with a NUL character instead of a newline in print0 mode,
+/// and only the file path is written in paths-only mode.
+///
 /// # Arguments
 ///
 /// * `args` - The command line arguments.
-/// * `filesize` - The size of the file.
-/// * `filepath` - The path of the file.
+/// * `record` - The file record with the sizes, path, and modification time of the file.
 /// * `metric_size_formatter` - The function to format the file size in metric units.
 /// * `binary_size_formatter` - The function to format the file size in binary units.
 ///
@@ -48,12 +122,53 @@ where
 #[inline(always)]
 pub fn format_print_file(
     args: &Args,
-    filesize: &u64,
-    filepath: &Path,
+    record: &FileRecord,
     metric_size_formatter: impl Fn(u64) -> String,
     binary_size_formatter: impl Fn(u64) -> String,
 ) -> Result<(), std::io::Error> {
-    // exclude directories, filter on files only
+    let filepath = record.path.as_path();
+    let terminator = if args.print0 { '\0' } else { '\n' };
+    if args.paths_only && !args.color {
+        return write_path_stdout(filepath, terminator);
+    }
+    let format_size = |size: u64| {
+        if args.metric_units {
+            format!("{:>9}", metric_size_formatter(size))
+        } else if args.binary_units {
+            format!("{:>10}", binary_size_formatter(size))
+        } else {
+            size.to_string()
+        }
+    };
+    let mut fmt_filesize = format_size(record.size);
+    // the optional allocated disk usage column is written after the apparent size column
+    if args.both_sizes {
+        fmt_filesize.push('\t');
+        match record.allocated {
+            Some(allocated) => fmt_filesize.push_str(&format_size(allocated)),
+            None => fmt_filesize.push('-'),
+        }
+    }
+    // the optional modification time column is written between the size and path columns
+    if args.mtime {
+        fmt_filesize.push('\t');
+        match record.modified {
+            Some(modified) => fmt_filesize.push_str(&format_timestamp(modified)),
+            None => fmt_filesize.push_str(&format!("{:<20}", "-")),
+        }
+    }
+
+    // duplicate hard links are marked in a column before the path column
+    // when all links are reported
+    if let (Some(LinkCount::All), Some(duplicate_link)) = (args.count_links, record.duplicate_link)
+    {
+        fmt_filesize.push_str(if duplicate_link {
+            "\thardlink"
+        } else {
+            "\t-       "
+        });
+    }
+
     if args.color {
         let fmt_filepath = match filepath.parent() {
             Some(ppath) => match filepath.file_name() {
@@ -69,29 +184,1042 @@ pub fn format_print_file(
             },
             None => String::from(""),
         };
-
-        let fmt_filesize: String;
-        if args.metric_units {
-            fmt_filesize = format!("{:>9}", metric_size_formatter(*filesize));
-            write_stdout(&fmt_filesize, &fmt_filepath)?;
-        } else if args.binary_units {
-            fmt_filesize = format!("{:>10}", binary_size_formatter(*filesize));
-            write_stdout(&fmt_filesize, &fmt_filepath)?;
+        if args.paths_only {
+            write!(std::io::stdout(), "{}{}", fmt_filepath, terminator)?;
         } else {
-            write_stdout(filesize, &fmt_filepath)?;
-        }
-    } else if args.metric_units {
-        write_stdout(
-            format!("{:>9}", metric_size_formatter(*filesize)),
-            filepath.display(),
-        )?;
-    } else if args.binary_units {
-        write_stdout(
-            format!("{:>10}", binary_size_formatter(*filesize)),
-            filepath.display(),
-        )?;
+            write_stdout_terminated(&fmt_filesize, &fmt_filepath, terminator)?;
+        }
     } else {
-        write_stdout(filesize, filepath.display())?;
+        write_stdout_terminated(&fmt_filesize, filepath.display(), terminator)?;
     }
     Ok(())
 }
+
+/// Quotes a CSV or TSV field with RFC 4180 rules.
+///
+/// Fields that contain the delimiter, a double quote, or a line break are wrapped
+/// in double quotes, and embedded double quotes are escaped by doubling them.
+#[inline(always)]
+fn quote_delimited_field<'a>(field: &'a str, delimiter: &str) -> std::borrow::Cow<'a, str> {
+    if field.contains(delimiter) || field.contains(['"', '\n', '\r']) {
+        std::borrow::Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        std::borrow::Cow::Borrowed(field)
+    }
+}
+
+/// Returns the (name, bytes) pairs of the size statistics in a report summary.
+#[inline(always)]
+fn summary_size_statistics(summary: &Summary) -> [(&'static str, u64); 6] {
+    [
+        ("total", summary.total),
+        ("mean", summary.mean),
+        ("median", summary.median),
+        ("p90", summary.p90),
+        ("p99", summary.p99),
+        (
+            "largest",
+            summary.largest.as_ref().map_or(0, |largest| largest.size),
+        ),
+    ]
+}
+
+/// The JSON serialization of a `FileRecord`.
+#[derive(Serialize)]
+struct JsonFileRecord<'a> {
+    path: std::borrow::Cow<'a, str>,
+    size: u64,
+    size_human: String,
+    allocated: Option<u64>,
+    #[serde(rename = "type")]
+    file_type: Option<&'a str>,
+    modified: Option<u64>,
+    readonly: Option<bool>,
+    duplicate_link: Option<bool>,
+}
+
+/// The JSON serialization of a `DuplicateSet`.
+#[derive(Serialize)]
+struct JsonDuplicateSet<'a> {
+    size: u64,
+    size_human: String,
+    files: usize,
+    reclaimable: u64,
+    paths: Vec<std::borrow::Cow<'a, str>>,
+}
+
+/// The JSON serialization of a `BudgetViolation`.
+#[derive(Serialize)]
+struct JsonBudgetViolation<'a> {
+    kind: &'static str,
+    pattern: &'a str,
+    limit: u64,
+    limit_human: String,
+    size: u64,
+    size_human: String,
+    path: Option<std::borrow::Cow<'a, str>>,
+}
+
+/// The JSON serialization of a `FileChange`.
+#[derive(Serialize)]
+struct JsonFileChange<'a> {
+    path: std::borrow::Cow<'a, str>,
+    status: &'static str,
+    old_size: Option<u64>,
+    new_size: Option<u64>,
+    delta: i64,
+    delta_human: String,
+}
+
+/// The JSON serialization of a `GroupTotal` with the group name under the `label` key,
+/// and the optional largest files of the group under the `largest_files` key.
+struct JsonGroupTotal<'a> {
+    label: &'a str,
+    group: &'a GroupTotal,
+    size_human: String,
+    share: f64,
+    largest_files: Option<Vec<LargestFile>>,
+}
+
+impl<'a> Serialize for JsonGroupTotal<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry(self.label, &self.group.name)?;
+        map.serialize_entry("files", &self.group.files)?;
+        map.serialize_entry("size", &self.group.total)?;
+        map.serialize_entry("size_human", &self.size_human)?;
+        map.serialize_entry("share", &self.share)?;
+        map.serialize_entry("largest", &self.group.largest)?;
+        if let Some(largest_files) = &self.largest_files {
+            map.serialize_entry("largest_files", largest_files)?;
+        }
+        map.end()
+    }
+}
+
+/// `ReportWriter` is a struct that writes size report records to the standard
+/// output stream in the output format that is requested on the command line.
+///
+/// The struct is safe to share across the threads of a parallel directory walk.
+/// Records are written atomically, and JSON array separators are written in the
+/// order that records reach the standard output stream.
+///
+/// # Usage
+///
+/// Call `write_header` once before the first record, `write_record` for each
+/// report record, and `write_footer` once after the last record.  The optional
+/// report summary is accumulated from the written records and is written in
+/// the footer.  Aggregate reports call `write_group` or `write_group_with_files`
+/// for each group, duplicate file reports call `write_duplicate_sets` once,
+/// snapshot diff reports call `write_changes` once, and size budget reports call
+/// `write_budget_violations` once, instead of `write_record`.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+///
+/// use clap::Parser;
+///
+/// use siz::args::Args;
+/// use siz::record::FileRecord;
+/// use siz::stdstreams::ReportWriter;
+///
+/// let args = Args::parse_from(vec!["siz", "--output", "ndjson", "."]);
+/// let report_writer = ReportWriter::new(&args).unwrap();
+/// report_writer.write_header().unwrap();
+/// report_writer
+///     .write_record(&FileRecord::from_size(10, PathBuf::from("foo.rs")))
+///     .unwrap();
+/// report_writer.write_footer().unwrap();
+/// ```
+pub struct ReportWriter<'a> {
+    args: &'a Args,
+    metric_size_formatter: Box<dyn Fn(u64) -> String + Send + Sync>,
+    binary_size_formatter: Box<dyn Fn(u64) -> String + Send + Sync>,
+    type_classifier: Option<TypeClassifier>,
+    record_count: AtomicUsize,
+    summary_builder: Option<Mutex<SummaryBuilder>>,
+    group_label: Option<&'static str>,
+}
+
+impl<'a> ReportWriter<'a> {
+    /// Constructs a new `ReportWriter` instance.
+    ///
+    /// # Parameters
+    ///
+    /// * `args`: A reference to an `Args` struct that contains the command line arguments.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` that contains a `ReportWriter` instance if the method succeeds, or an error
+    /// if the file type classifier for the machine-readable output formats cannot be built.
+    pub fn new(args: &'a Args) -> Result<Self> {
+        // the file type classifier is only used in machine-readable output formats
+        let type_classifier = match args.output {
+            OutputFormat::Text => None,
+            OutputFormat::Json | OutputFormat::Ndjson => {
+                Some(TypeClassifier::with_user_types(&args.type_add)?)
+            }
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                if args.columns.contains(&Column::Type) {
+                    Some(TypeClassifier::with_user_types(&args.type_add)?)
+                } else {
+                    None
+                }
+            }
+        };
+        Ok(Self {
+            args,
+            metric_size_formatter: Box::new(build_metric_size_formatter()),
+            binary_size_formatter: Box::new(build_binary_size_formatter()),
+            type_classifier,
+            record_count: AtomicUsize::new(0),
+            summary_builder: if args.summary {
+                Some(Mutex::new(SummaryBuilder::with_roots(&args.paths)))
+            } else {
+                None
+            },
+            group_label: if args.by_type {
+                Some("type")
+            } else if args.by_ignore {
+                Some("class")
+            } else if args.by_extension {
+                Some("extension")
+            } else {
+                None
+            },
+        })
+    }
+
+    /// Writes the report header to stdout.
+    pub fn write_header(&self) -> Result<(), std::io::Error> {
+        match self.args.output {
+            OutputFormat::Json => {
+                // the file records are nested in an object with the summary
+                if self.summary_builder.is_some() {
+                    write!(std::io::stdout(), "{{\"files\": [")?;
+                } else {
+                    write!(std::io::stdout(), "[")?;
+                }
+            }
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                if let Some(group_label) = self.group_label {
+                    writeln!(
+                        std::io::stdout(),
+                        "{1}{0}files{0}bytes{0}size{0}share{0}largest_bytes{0}largest_path",
+                        self.delimiter(),
+                        group_label
+                    )?;
+                    return Ok(());
+                }
+                if self.args.budget.is_some()
+                    || !self.args.budget_each.is_empty()
+                    || !self.args.budget_total.is_empty()
+                {
+                    writeln!(
+                        std::io::stdout(),
+                        "kind{0}pattern{0}limit_bytes{0}bytes{0}path",
+                        self.delimiter()
+                    )?;
+                    return Ok(());
+                }
+                if self.args.diff.is_some() {
+                    writeln!(
+                        std::io::stdout(),
+                        "status{0}path{0}old_bytes{0}new_bytes{0}delta_bytes",
+                        self.delimiter()
+                    )?;
+                    return Ok(());
+                }
+                if self.args.duplicates {
+                    writeln!(
+                        std::io::stdout(),
+                        "set{0}bytes{0}size{0}path",
+                        self.delimiter()
+                    )?;
+                    return Ok(());
+                }
+                let header = self
+                    .args
+                    .columns
+                    .iter()
+                    .map(|column| column.header())
+                    .collect::<Vec<&str>>()
+                    .join(self.delimiter());
+                writeln!(std::io::stdout(), "{}", header)?;
+            }
+            OutputFormat::Text | OutputFormat::Ndjson => (),
+        }
+        Ok(())
+    }
+
+    /// Writes a single report record to stdout.
+    pub fn write_record(&self, record: &FileRecord) -> Result<(), std::io::Error> {
+        if let Some(summary_builder) = &self.summary_builder {
+            if let Ok(mut summary_builder) = summary_builder.lock() {
+                summary_builder.add(record);
+            }
+        }
+        match self.args.output {
+            OutputFormat::Text => format_print_file(
+                self.args,
+                record,
+                &self.metric_size_formatter,
+                &self.binary_size_formatter,
+            ),
+            OutputFormat::Json => self.write_json_array_item(&self.format_json_record(record)?),
+            OutputFormat::Ndjson => {
+                let json = self.format_json_record(record)?;
+                writeln!(std::io::stdout(), "{}", json)
+            }
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                let row = self.format_delimited_record(record);
+                writeln!(std::io::stdout(), "{}", row)
+            }
+        }
+    }
+
+    /// Writes a single aggregate report group to stdout.
+    ///
+    /// # Parameters
+    ///
+    /// * `group`: A reference to the `GroupTotal` of the group.
+    /// * `grand_total`: The total size of all groups in bytes, used for the group share.
+    pub fn write_group(&self, group: &GroupTotal, grand_total: u64) -> Result<(), std::io::Error> {
+        self.write_group_inner(group, grand_total, None)
+    }
+
+    /// Writes a single aggregate report group and the largest files of the group to stdout.
+    ///
+    /// The largest files are listed below the group in the text output format and under
+    /// the `largest_files` key in the JSON output formats.  The CSV and TSV output formats
+    /// include the largest file of the group only.
+    ///
+    /// # Parameters
+    ///
+    /// * `group`: A reference to the `GroupTotal` of the group.
+    /// * `grand_total`: The total size of all groups in bytes, used for the group share.
+    /// * `largest_files`: A slice of the largest files of the group in report order.
+    pub fn write_group_with_files(
+        &self,
+        group: &GroupTotal,
+        grand_total: u64,
+        largest_files: &[FileRecord],
+    ) -> Result<(), std::io::Error> {
+        self.write_group_inner(group, grand_total, Some(largest_files))
+    }
+
+    fn write_group_inner(
+        &self,
+        group: &GroupTotal,
+        grand_total: u64,
+        largest_files: Option<&[FileRecord]>,
+    ) -> Result<(), std::io::Error> {
+        let group_label = self.group_label.unwrap_or("group");
+        let share = group.share(grand_total);
+        match self.args.output {
+            OutputFormat::Text => {
+                let mut stdout = std::io::stdout().lock();
+                writeln!(
+                    stdout,
+                    "{}\t{}\t{:>6.2}%\t{}",
+                    self.format_padded_text_size(group.total),
+                    group.files,
+                    share,
+                    group.name
+                )?;
+                for record in largest_files.unwrap_or_default() {
+                    writeln!(
+                        stdout,
+                        "  {}\t{}",
+                        self.format_padded_text_size(record.size),
+                        record.path.display()
+                    )?;
+                }
+                Ok(())
+            }
+            OutputFormat::Json | OutputFormat::Ndjson => {
+                let json = serde_json::to_string(&JsonGroupTotal {
+                    label: group_label,
+                    group,
+                    size_human: self.format_human_size(group.total),
+                    // round the percentage to two decimal places
+                    share: (share * 100.0).round() / 100.0,
+                    largest_files: largest_files.map(|records| {
+                        records
+                            .iter()
+                            .map(|record| LargestFile {
+                                path: record.path.clone(),
+                                size: record.size,
+                            })
+                            .collect()
+                    }),
+                })?;
+                if self.args.output == OutputFormat::Json {
+                    self.write_json_array_item(&json)
+                } else {
+                    writeln!(std::io::stdout(), "{}", json)
+                }
+            }
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                let delimiter = self.delimiter();
+                writeln!(
+                    std::io::stdout(),
+                    "{1}{0}{2}{0}{3}{0}{4}{0}{5:.2}{0}{6}{0}{7}",
+                    delimiter,
+                    quote_delimited_field(&group.name, delimiter),
+                    group.files,
+                    group.total,
+                    self.format_human_size(group.total),
+                    share,
+                    group.largest.size,
+                    quote_delimited_field(&group.largest.path.to_string_lossy(), delimiter)
+                )
+            }
+        }
+    }
+
+    /// Writes the duplicate file sets of a duplicate file report to stdout.
+    ///
+    /// The text output format includes the duplicate set count, duplicate file count,
+    /// and the total reclaimable size after the sets.
+    ///
+    /// # Parameters
+    ///
+    /// * `duplicate_sets`: A slice of the `DuplicateSet` instances in report order.
+    pub fn write_duplicate_sets(
+        &self,
+        duplicate_sets: &[DuplicateSet],
+    ) -> Result<(), std::io::Error> {
+        let mut stdout = std::io::stdout().lock();
+        match self.args.output {
+            OutputFormat::Text => {
+                for (i, duplicate_set) in duplicate_sets.iter().enumerate() {
+                    // the sets are separated by a blank line
+                    if i > 0 {
+                        writeln!(stdout)?;
+                    }
+                    let fmt_size = self.format_padded_text_size(duplicate_set.size);
+                    for path in &duplicate_set.paths {
+                        writeln!(stdout, "{}\t{}", fmt_size, path.display())?;
+                    }
+                }
+                let files: usize = duplicate_sets.iter().map(|set| set.paths.len()).sum();
+                let reclaimable: u64 = duplicate_sets.iter().map(|set| set.reclaimable).sum();
+                writeln!(stdout)?;
+                writeln!(stdout, "duplicate_sets\t{}", duplicate_sets.len())?;
+                writeln!(stdout, "duplicate_files\t{}", files)?;
+                writeln!(
+                    stdout,
+                    "reclaimable\t{}",
+                    self.format_text_size(reclaimable)
+                )?;
+            }
+            OutputFormat::Json | OutputFormat::Ndjson => {
+                // release the stdout lock, the JSON array items acquire it
+                drop(stdout);
+                for duplicate_set in duplicate_sets {
+                    let json = serde_json::to_string(&JsonDuplicateSet {
+                        size: duplicate_set.size,
+                        size_human: self.format_human_size(duplicate_set.size),
+                        files: duplicate_set.paths.len(),
+                        reclaimable: duplicate_set.reclaimable,
+                        paths: duplicate_set
+                            .paths
+                            .iter()
+                            .map(|path| path.to_string_lossy())
+                            .collect(),
+                    })?;
+                    if self.args.output == OutputFormat::Json {
+                        self.write_json_array_item(&json)?;
+                    } else {
+                        writeln!(std::io::stdout(), "{}", json)?;
+                    }
+                }
+            }
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                let delimiter = self.delimiter();
+                for (i, duplicate_set) in duplicate_sets.iter().enumerate() {
+                    let fmt_size = self.format_human_size(duplicate_set.size);
+                    for path in &duplicate_set.paths {
+                        writeln!(
+                            stdout,
+                            "{1}{0}{2}{0}{3}{0}{4}",
+                            delimiter,
+                            i + 1,
+                            duplicate_set.size,
+                            fmt_size,
+                            quote_delimited_field(&path.to_string_lossy(), delimiter)
+                        )?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the size budget rule violations of a size budget report to stdout.
+    ///
+    /// The text output format includes the budget rule count and the budget
+    /// violation count after the violations.
+    ///
+    /// # Parameters
+    ///
+    /// * `violations`: A slice of the `BudgetViolation` instances in report order.
+    /// * `rule_count`: The number of budget rules that were evaluated.
+    pub fn write_budget_violations(
+        &self,
+        violations: &[BudgetViolation],
+        rule_count: usize,
+    ) -> Result<(), std::io::Error> {
+        let mut stdout = std::io::stdout().lock();
+        match self.args.output {
+            OutputFormat::Text => {
+                for violation in violations {
+                    let fmt_path = violation
+                        .path
+                        .as_ref()
+                        .map(|path| format!("\t{}", path.display()))
+                        .unwrap_or_default();
+                    writeln!(
+                        stdout,
+                        "{}\t{}\t{} > {}{}",
+                        violation.rule.kind.as_str(),
+                        violation.rule.pattern,
+                        self.format_text_size(violation.size),
+                        self.format_text_size(violation.rule.limit),
+                        fmt_path
+                    )?;
+                }
+                if !violations.is_empty() {
+                    writeln!(stdout)?;
+                }
+                writeln!(stdout, "budget_rules\t{}", rule_count)?;
+                writeln!(stdout, "budget_violations\t{}", violations.len())?;
+            }
+            OutputFormat::Json | OutputFormat::Ndjson => {
+                // release the stdout lock, the JSON array items acquire it
+                drop(stdout);
+                for violation in violations {
+                    let json = serde_json::to_string(&JsonBudgetViolation {
+                        kind: violation.rule.kind.as_str(),
+                        pattern: &violation.rule.pattern,
+                        limit: violation.rule.limit,
+                        limit_human: self.format_human_size(violation.rule.limit),
+                        size: violation.size,
+                        size_human: self.format_human_size(violation.size),
+                        path: violation.path.as_ref().map(|path| path.to_string_lossy()),
+                    })?;
+                    if self.args.output == OutputFormat::Json {
+                        self.write_json_array_item(&json)?;
+                    } else {
+                        writeln!(std::io::stdout(), "{}", json)?;
+                    }
+                }
+            }
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                let delimiter = self.delimiter();
+                for violation in violations {
+                    writeln!(
+                        stdout,
+                        "{1}{0}{2}{0}{3}{0}{4}{0}{5}",
+                        delimiter,
+                        violation.rule.kind.as_str(),
+                        quote_delimited_field(&violation.rule.pattern, delimiter),
+                        violation.rule.limit,
+                        violation.size,
+                        violation
+                            .path
+                            .as_ref()
+                            .map(|path| {
+                                quote_delimited_field(&path.to_string_lossy(), delimiter)
+                                    .into_owned()
+                            })
+                            .unwrap_or_default()
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the file changes of a snapshot diff report to stdout.
+    ///
+    /// The text output format includes the file count and the total size change of
+    /// each change kind, and the net size change, after the file changes.
+    ///
+    /// # Parameters
+    ///
+    /// * `changes`: A slice of the `FileChange` instances in report order.
+    pub fn write_changes(&self, changes: &[FileChange]) -> Result<(), std::io::Error> {
+        let mut stdout = std::io::stdout().lock();
+        match self.args.output {
+            OutputFormat::Text => {
+                for change in changes {
+                    writeln!(
+                        stdout,
+                        "{}\t{:<7}\t{}",
+                        self.format_padded_text_delta(change.delta()),
+                        change.kind.as_str(),
+                        change.path.display()
+                    )?;
+                }
+                writeln!(stdout)?;
+                for kind in [
+                    ChangeKind::Added,
+                    ChangeKind::Removed,
+                    ChangeKind::Grown,
+                    ChangeKind::Shrunk,
+                ] {
+                    let (files, delta) = changes
+                        .iter()
+                        .filter(|change| change.kind == kind)
+                        .fold((0, 0), |(files, delta), change| {
+                            (files + 1, delta + change.delta())
+                        });
+                    writeln!(
+                        stdout,
+                        "{}\t{}\t{}",
+                        kind.as_str(),
+                        files,
+                        self.format_text_delta(delta)
+                    )?;
+                }
+                let net: i64 = changes.iter().map(|change| change.delta()).sum();
+                writeln!(stdout, "net\t\t{}", self.format_text_delta(net))?;
+            }
+            OutputFormat::Json | OutputFormat::Ndjson => {
+                // release the stdout lock, the JSON array items acquire it
+                drop(stdout);
+                for change in changes {
+                    let json = serde_json::to_string(&JsonFileChange {
+                        path: change.path.to_string_lossy(),
+                        status: change.kind.as_str(),
+                        old_size: change.old_size,
+                        new_size: change.new_size,
+                        delta: change.delta(),
+                        delta_human: self.format_human_delta(change.delta()),
+                    })?;
+                    if self.args.output == OutputFormat::Json {
+                        self.write_json_array_item(&json)?;
+                    } else {
+                        writeln!(std::io::stdout(), "{}", json)?;
+                    }
+                }
+            }
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                let delimiter = self.delimiter();
+                let optional_size =
+                    |size: Option<u64>| size.map(|size| size.to_string()).unwrap_or_default();
+                for change in changes {
+                    writeln!(
+                        stdout,
+                        "{1}{0}{2}{0}{3}{0}{4}{0}{5}",
+                        delimiter,
+                        change.kind.as_str(),
+                        quote_delimited_field(&change.path.to_string_lossy(), delimiter),
+                        optional_size(change.old_size),
+                        optional_size(change.new_size),
+                        change.delta()
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the report footer, including the optional report summary, to stdout.
+    pub fn write_footer(&self) -> Result<(), std::io::Error> {
+        let summary = match &self.summary_builder {
+            Some(summary_builder) => match summary_builder.lock() {
+                Ok(mut summary_builder) => Some(std::mem::take(&mut *summary_builder).build()),
+                Err(_) => None,
+            },
+            None => None,
+        };
+        let mut stdout = std::io::stdout().lock();
+        match self.args.output {
+            OutputFormat::Json => {
+                if self.record_count.load(Ordering::SeqCst) == 0 {
+                    write!(stdout, "]")?;
+                } else {
+                    write!(stdout, "\n]")?;
+                }
+                match summary {
+                    Some(summary) => writeln!(
+                        stdout,
+                        ",\n\"summary\": {}}}",
+                        serde_json::to_string(&summary)?
+                    )?,
+                    None => writeln!(stdout)?,
+                }
+            }
+            OutputFormat::Ndjson => {
+                if let Some(summary) = summary {
+                    writeln!(
+                        stdout,
+                        "{{\"summary\":{}}}",
+                        serde_json::to_string(&summary)?
+                    )?;
+                }
+            }
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                if let Some(summary) = summary {
+                    // the summary is a separate two column table that follows the report
+                    let delimiter = self.delimiter();
+                    writeln!(stdout)?;
+                    writeln!(stdout, "statistic{}value", delimiter)?;
+                    writeln!(stdout, "files{}{}", delimiter, summary.files)?;
+                    for (statistic, value) in summary_size_statistics(&summary) {
+                        writeln!(stdout, "{}{}{}", statistic, delimiter, value)?;
+                    }
+                    if let Some(largest) = &summary.largest {
+                        writeln!(
+                            stdout,
+                            "largest_path{}{}",
+                            delimiter,
+                            quote_delimited_field(&largest.path.to_string_lossy(), delimiter)
+                        )?;
+                    }
+                    // the per-device breakdown is a separate table that follows the summary
+                    if !summary.devices.is_empty() {
+                        writeln!(stdout)?;
+                        writeln!(stdout, "device{0}files{0}total{0}path", delimiter)?;
+                        for device_total in &summary.devices {
+                            writeln!(
+                                stdout,
+                                "{1}{0}{2}{0}{3}{0}{4}",
+                                delimiter,
+                                device_total.device,
+                                device_total.files,
+                                device_total.total,
+                                quote_delimited_field(
+                                    &device_total.path.to_string_lossy(),
+                                    delimiter
+                                )
+                            )?;
+                        }
+                    }
+                    // the per-root breakdown is a separate table that follows the summary
+                    if !summary.roots.is_empty() {
+                        writeln!(stdout)?;
+                        writeln!(stdout, "root{0}files{0}total", delimiter)?;
+                        for root_total in &summary.roots {
+                            writeln!(
+                                stdout,
+                                "{1}{0}{2}{0}{3}",
+                                delimiter,
+                                quote_delimited_field(
+                                    &root_total.path.to_string_lossy(),
+                                    delimiter
+                                ),
+                                root_total.files,
+                                root_total.total
+                            )?;
+                        }
+                    }
+                }
+            }
+            OutputFormat::Text => {
+                if let Some(summary) = summary {
+                    writeln!(stdout)?;
+                    writeln!(stdout, "files\t{}", summary.files)?;
+                    for (statistic, value) in summary_size_statistics(&summary) {
+                        writeln!(stdout, "{}\t{}", statistic, self.format_text_size(value))?;
+                    }
+                    if let Some(largest) = &summary.largest {
+                        writeln!(stdout, "largest_path\t{}", largest.path.display())?;
+                    }
+                    if !summary.devices.is_empty() {
+                        writeln!(stdout)?;
+                        writeln!(stdout, "device\tfiles\ttotal\tpath")?;
+                        for device_total in &summary.devices {
+                            writeln!(
+                                stdout,
+                                "{}\t{}\t{}\t{}",
+                                device_total.device,
+                                device_total.files,
+                                self.format_text_size(device_total.total),
+                                device_total.path.display()
+                            )?;
+                        }
+                    }
+                    if !summary.roots.is_empty() {
+                        writeln!(stdout)?;
+                        writeln!(stdout, "root\tfiles\ttotal")?;
+                        for root_total in &summary.roots {
+                            writeln!(
+                                stdout,
+                                "{}\t{}\t{}",
+                                root_total.path.display(),
+                                root_total.files,
+                                self.format_text_size(root_total.total)
+                            )?;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes an item of the JSON array report to stdout.
+    #[inline(always)]
+    fn write_json_array_item(&self, json: &str) -> Result<(), std::io::Error> {
+        // hold the stdout lock across the record count update so that
+        // the array separators are written in record order across threads
+        let mut stdout = std::io::stdout().lock();
+        if self.record_count.fetch_add(1, Ordering::SeqCst) == 0 {
+            write!(stdout, "\n  {}", json)
+        } else {
+            write!(stdout, ",\n  {}", json)
+        }
+    }
+
+    /// Formats a size in the text output format with the human-readable size
+    /// column widths of the file report.
+    #[inline(always)]
+    fn format_padded_text_size(&self, size: u64) -> String {
+        if self.args.metric_units {
+            format!("{:>9}", (self.metric_size_formatter)(size))
+        } else if self.args.binary_units {
+            format!("{:>10}", (self.binary_size_formatter)(size))
+        } else {
+            size.to_string()
+        }
+    }
+
+    /// Formats a signed size change in the text output format with the human-readable
+    /// size column widths of the file report and an explicit sign.
+    #[inline(always)]
+    fn format_padded_text_delta(&self, delta: i64) -> String {
+        let fmt_delta = self.format_text_delta(delta);
+        if self.args.metric_units {
+            format!("{:>10}", fmt_delta)
+        } else if self.args.binary_units {
+            format!("{:>11}", fmt_delta)
+        } else {
+            fmt_delta
+        }
+    }
+
+    /// Formats a signed size change in the text output format with an explicit sign.
+    #[inline(always)]
+    fn format_text_delta(&self, delta: i64) -> String {
+        let sign = if delta < 0 { "-" } else { "+" };
+        format!("{}{}", sign, self.format_text_size(delta.unsigned_abs()))
+    }
+
+    /// Formats a signed size change in human-readable units with an explicit sign.
+    #[inline(always)]
+    fn format_human_delta(&self, delta: i64) -> String {
+        let sign = if delta < 0 { "-" } else { "+" };
+        format!("{}{}", sign, self.format_human_size(delta.unsigned_abs()))
+    }
+
+    #[inline(always)]
+    fn format_text_size(&self, size: u64) -> String {
+        if self.args.metric_units {
+            (self.metric_size_formatter)(size)
+        } else if self.args.binary_units {
+            (self.binary_size_formatter)(size)
+        } else {
+            size.to_string()
+        }
+    }
+
+    #[inline(always)]
+    fn delimiter(&self) -> &'static str {
+        match self.args.output {
+            OutputFormat::Tsv => "\t",
+            _ => ",",
+        }
+    }
+
+    #[inline(always)]
+    fn classify(&self, record: &FileRecord) -> Option<&str> {
+        match &self.type_classifier {
+            // file types do not apply to cumulative directory size totals
+            Some(classifier) if !self.args.dirs => classifier.classify(&record.path),
+            _ => None,
+        }
+    }
+
+    #[inline(always)]
+    fn format_delimited_record(&self, record: &FileRecord) -> String {
+        let delimiter = self.delimiter();
+        let mut row = String::new();
+        for (i, column) in self.args.columns.iter().enumerate() {
+            if i > 0 {
+                row.push_str(delimiter);
+            }
+            let field = match column {
+                Column::Bytes => record.size.to_string(),
+                Column::Size => self.format_human_size(record.size),
+                Column::Allocated => record.allocated.map(|a| a.to_string()).unwrap_or_default(),
+                Column::Path => record.path.to_string_lossy().into_owned(),
+                Column::Ext => match record.path.extension() {
+                    Some(ext) if !self.args.dirs => ext.to_string_lossy().into_owned(),
+                    _ => String::new(),
+                },
+                Column::Type => self.classify(record).unwrap_or_default().to_string(),
+                Column::Mtime => record.modified.map(format_timestamp).unwrap_or_default(),
+                Column::Link => record
+                    .duplicate_link
+                    .map(|duplicate_link| duplicate_link.to_string())
+                    .unwrap_or_default(),
+            };
+            row.push_str(&quote_delimited_field(&field, delimiter));
+        }
+        row
+    }
+
+    #[inline(always)]
+    fn format_human_size(&self, size: u64) -> String {
+        // binary units are the default human-readable format in machine-readable output
+        if self.args.metric_units {
+            (self.metric_size_formatter)(size)
+        } else {
+            (self.binary_size_formatter)(size)
+        }
+    }
+
+    #[inline(always)]
+    fn format_json_record(&self, record: &FileRecord) -> Result<String, std::io::Error> {
+        let json_record = JsonFileRecord {
+            path: record.path.to_string_lossy(),
+            size: record.size,
+            size_human: self.format_human_size(record.size),
+            allocated: record.allocated,
+            file_type: self.classify(record),
+            modified: record.modified_unix_seconds(),
+            readonly: record.readonly,
+            duplicate_link: record.duplicate_link,
+        };
+        Ok(serde_json::to_string(&json_record)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_format_json_record() -> Result<()> {
+        let args = Args::parse_from(vec!["siz", "--output", "json", "."]);
+        let report_writer = ReportWriter::new(&args)?;
+        let record = FileRecord {
+            size: 2048,
+            path: PathBuf::from("a/b\tc\"d.rs"),
+            modified: Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+            readonly: Some(false),
+            allocated: Some(4096),
+            apparent: Some(2048),
+            duplicate_link: Some(true),
+            device: None,
+            inode: None,
+        };
+        assert_eq!(
+            report_writer.format_json_record(&record)?,
+            r#"{"path":"a/b\tc\"d.rs","size":2048,"size_human":"2 KiB","allocated":4096,"type":"rust","modified":1700000000,"readonly":false,"duplicate_link":true}"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_json_record_metric_units_directory() -> Result<()> {
+        let args = Args::parse_from(vec!["siz", "--output", "ndjson", "-m", "--dirs", "."]);
+        let report_writer = ReportWriter::new(&args)?;
+        let record = FileRecord::from_size(2000, PathBuf::from("a.rs"));
+        assert_eq!(
+            report_writer.format_json_record(&record)?,
+            r#"{"path":"a.rs","size":2000,"size_human":"2 kB","allocated":null,"type":null,"modified":null,"readonly":null,"duplicate_link":null}"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_group_total() -> Result<()> {
+        let group = GroupTotal {
+            name: "rust".to_string(),
+            files: 2,
+            total: 3000,
+            largest: LargestFile {
+                path: PathBuf::from("a.rs"),
+                size: 2000,
+            },
+        };
+        let json_group = JsonGroupTotal {
+            label: "type",
+            group: &group,
+            size_human: "3 kB".to_string(),
+            share: 12.5,
+            largest_files: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&json_group)?,
+            r#"{"type":"rust","files":2,"size":3000,"size_human":"3 kB","share":12.5,"largest":{"path":"a.rs","size":2000}}"#
+        );
+        let json_class = JsonGroupTotal {
+            label: "class",
+            largest_files: Some(vec![group.largest.clone()]),
+            ..json_group
+        };
+        assert_eq!(
+            serde_json::to_string(&json_class)?,
+            r#"{"class":"rust","files":2,"size":3000,"size_human":"3 kB","share":12.5,"largest":{"path":"a.rs","size":2000},"largest_files":[{"path":"a.rs","size":2000}]}"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_quote_delimited_field() {
+        assert_eq!(quote_delimited_field("a/b.rs", ","), "a/b.rs");
+        assert_eq!(quote_delimited_field("a,b.rs", ","), "\"a,b.rs\"");
+        assert_eq!(quote_delimited_field("a,b.rs", "\t"), "a,b.rs");
+        assert_eq!(quote_delimited_field("a\tb.rs", "\t"), "\"a\tb.rs\"");
+        assert_eq!(quote_delimited_field("a\"b.rs", ","), "\"a\"\"b.rs\"");
+        assert_eq!(quote_delimited_field("a\nb.rs", "\t"), "\"a\nb.rs\"");
+    }
+
+    #[test]
+    fn test_format_delimited_record() -> Result<()> {
+        let args = Args::parse_from(vec![
+            "siz",
+            "--output",
+            "csv",
+            "--columns",
+            "bytes,size,allocated,path,ext,type,mtime,link",
+            ".",
+        ]);
+        let report_writer = ReportWriter::new(&args)?;
+        let record = FileRecord {
+            size: 2048,
+            path: PathBuf::from("a/b,\"c\".tar.gz"),
+            modified: Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+            readonly: Some(false),
+            allocated: Some(4096),
+            apparent: Some(2048),
+            duplicate_link: Some(true),
+            device: None,
+            inode: None,
+        };
+        assert_eq!(
+            report_writer.format_delimited_record(&record),
+            "2048,2 KiB,4096,\"a/b,\"\"c\"\".tar.gz\",gz,tar,2023-11-14T22:13:20Z,true"
+        );
+
+        let args = Args::parse_from(vec!["siz", "--output", "tsv", "."]);
+        let report_writer = ReportWriter::new(&args)?;
+        assert_eq!(
+            report_writer.format_delimited_record(&record),
+            "2048\t\"a/b,\"\"c\"\".tar.gz\""
+        );
+        Ok(())
+    }
+}
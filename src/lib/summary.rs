@@ -0,0 +1,379 @@
+//! This module provides support for size report summary statistics.
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Serialize, Serializer};
+
+use crate::record::FileRecord;
+
+/// `Summary` is a struct that represents the summary statistics of a size report.
+///
+/// Percentiles are calculated with the nearest-rank method, and the median is the
+/// 50th percentile.
+///
+/// # Fields
+///
+/// * `files`: The number of files in the report.
+/// * `total`: The total size of the files in bytes.
+/// * `mean`: The mean file size in bytes, rounded down.
+/// * `median`: The median file size in bytes.
+/// * `p90`: The 90th percentile file size in bytes.
+/// * `p99`: The 99th percentile file size in bytes.
+/// * `largest`: The optional largest file in the report.
+/// * `devices`: The per-device totals, in device ID order.  This is empty unless the
+///   files in the report are on more than one device.
+/// * `roots`: The per-root path totals, in path argument order.  This is empty unless the
+///   report has more than one path argument.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Summary {
+    pub files: usize,
+    pub total: u64,
+    pub mean: u64,
+    pub median: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub largest: Option<LargestFile>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub devices: Vec<DeviceTotal>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub roots: Vec<RootTotal>,
+}
+
+/// `LargestFile` is a struct that represents the largest file in a size report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LargestFile {
+    #[serde(serialize_with = "serialize_path_lossy")]
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// `DeviceTotal` is a struct that represents the file count and total size of the files
+/// in a size report that are on a single device.
+///
+/// # Fields
+///
+/// * `device`: The device ID.
+/// * `files`: The number of files on the device.
+/// * `total`: The total size of the files on the device in bytes.
+/// * `path`: The deepest common directory of the files on the device.  This is typically
+///   the mount point of the file system, or the walk root for the file system of the root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeviceTotal {
+    pub device: u64,
+    pub files: usize,
+    pub total: u64,
+    #[serde(serialize_with = "serialize_path_lossy")]
+    pub path: PathBuf,
+}
+
+/// `RootTotal` is a struct that represents the file count and total size of the files
+/// in a size report that are found under a single path argument.
+///
+/// # Fields
+///
+/// * `path`: The path argument.
+/// * `files`: The number of files under the path.
+/// * `total`: The total size of the files under the path in bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RootTotal {
+    #[serde(serialize_with = "serialize_path_lossy")]
+    pub path: PathBuf,
+    pub files: usize,
+    pub total: u64,
+}
+
+/// `SummaryBuilder` is a struct that accumulates report records and builds a `Summary`.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+///
+/// use siz::record::FileRecord;
+/// use siz::summary::SummaryBuilder;
+///
+/// let mut builder = SummaryBuilder::default();
+/// builder.add(&FileRecord::from_size(10, PathBuf::from("a")));
+/// builder.add(&FileRecord::from_size(30, PathBuf::from("b")));
+/// let summary = builder.build();
+/// assert_eq!(summary.files, 2);
+/// assert_eq!(summary.total, 40);
+/// assert_eq!(summary.mean, 20);
+/// ```
+#[derive(Debug, Default)]
+pub struct SummaryBuilder {
+    sizes: Vec<u64>,
+    largest: Option<LargestFile>,
+    devices: BTreeMap<u64, DeviceTotal>,
+    roots: Vec<RootTotal>,
+}
+
+impl SummaryBuilder {
+    /// Returns a `SummaryBuilder` that also accumulates the per-root path totals of the
+    /// path arguments `roots`.  A record is counted under the first root path that
+    /// contains the record path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    ///
+    /// use siz::record::FileRecord;
+    /// use siz::summary::SummaryBuilder;
+    ///
+    /// let mut builder = SummaryBuilder::with_roots(&[PathBuf::from("a"), PathBuf::from("b")]);
+    /// builder.add(&FileRecord::from_size(10, PathBuf::from("a/one.txt")));
+    /// builder.add(&FileRecord::from_size(30, PathBuf::from("b/two.txt")));
+    /// let summary = builder.build();
+    /// assert_eq!(summary.roots[0].total, 10);
+    /// assert_eq!(summary.roots[1].total, 30);
+    /// ```
+    pub fn with_roots(roots: &[PathBuf]) -> Self {
+        Self {
+            roots: roots
+                .iter()
+                .map(|root| RootTotal {
+                    path: root.clone(),
+                    files: 0,
+                    total: 0,
+                })
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Adds a report record to the summary statistics.
+    #[inline(always)]
+    pub fn add(&mut self, record: &FileRecord) {
+        self.sizes.push(record.size);
+        match &self.largest {
+            Some(largest) if largest.size >= record.size => (),
+            _ => {
+                self.largest = Some(LargestFile {
+                    path: record.path.clone(),
+                    size: record.size,
+                })
+            }
+        }
+        if let Some(device) = record.device {
+            let parent = record.path.parent().unwrap_or(&record.path);
+            match self.devices.get_mut(&device) {
+                Some(device_total) => {
+                    device_total.files += 1;
+                    device_total.total += record.size;
+                    if !parent.starts_with(&device_total.path) {
+                        device_total.path = common_ancestor(&device_total.path, parent);
+                    }
+                }
+                None => {
+                    self.devices.insert(
+                        device,
+                        DeviceTotal {
+                            device,
+                            files: 1,
+                            total: record.size,
+                            path: parent.to_path_buf(),
+                        },
+                    );
+                }
+            }
+        }
+        if let Some(root_total) = self
+            .roots
+            .iter_mut()
+            .find(|root_total| record.path.starts_with(&root_total.path))
+        {
+            root_total.files += 1;
+            root_total.total += record.size;
+        }
+    }
+
+    /// Consumes the `SummaryBuilder` and returns the `Summary` of the added records.
+    pub fn build(mut self) -> Summary {
+        self.sizes.sort_unstable();
+        let files = self.sizes.len();
+        let total: u64 = self.sizes.iter().sum();
+        Summary {
+            files,
+            total,
+            mean: if files == 0 { 0 } else { total / files as u64 },
+            median: percentile(&self.sizes, 50),
+            p90: percentile(&self.sizes, 90),
+            p99: percentile(&self.sizes, 99),
+            largest: self.largest,
+            // the breakdown is only reported when more than one device is crossed
+            devices: if self.devices.len() > 1 {
+                self.devices.into_values().collect()
+            } else {
+                Vec::new()
+            },
+            // the breakdown is only reported when more than one path is walked
+            roots: if self.roots.len() > 1 {
+                self.roots
+            } else {
+                Vec::new()
+            },
+        }
+    }
+}
+
+/// Serializes a path as a string, with lossy conversion of non-UTF-8 paths.
+fn serialize_path_lossy<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&path.to_string_lossy())
+}
+
+/// Returns the deepest common ancestor path of the paths `a` and `b`.
+fn common_ancestor(a: &Path, b: &Path) -> PathBuf {
+    a.components()
+        .zip(b.components())
+        .take_while(|(a_component, b_component)| a_component == b_component)
+        .map(|(component, _)| component)
+        .collect()
+}
+
+/// Returns the nearest-rank `pct` percentile value of the ascending sorted `sizes`
+/// slice, or zero when the slice is empty.
+#[inline(always)]
+fn percentile(sizes: &[u64], pct: usize) -> u64 {
+    if sizes.is_empty() {
+        return 0;
+    }
+    // nearest rank = ceil(pct / 100 * n), as a one-based index
+    let rank = ((pct * sizes.len() + 99) / 100).max(1);
+    sizes[rank - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_percentile() {
+        let sizes: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&sizes, 50), 50);
+        assert_eq!(percentile(&sizes, 90), 90);
+        assert_eq!(percentile(&sizes, 99), 99);
+        assert_eq!(percentile(&[7], 99), 7);
+        assert_eq!(percentile(&[1, 2, 3], 50), 2);
+        assert_eq!(percentile(&[], 50), 0);
+    }
+
+    #[test]
+    fn test_summary_builder() {
+        let mut builder = SummaryBuilder::default();
+        for (size, path) in [(5, "a"), (100, "b"), (1, "c"), (20, "d")] {
+            builder.add(&FileRecord::from_size(size, PathBuf::from(path)));
+        }
+        assert_eq!(
+            builder.build(),
+            Summary {
+                files: 4,
+                total: 126,
+                mean: 31,
+                median: 5,
+                p90: 100,
+                p99: 100,
+                largest: Some(LargestFile {
+                    path: PathBuf::from("b"),
+                    size: 100
+                }),
+                devices: Vec::new(),
+                roots: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_summary_builder_devices() {
+        let mut builder = SummaryBuilder::default();
+        for (size, path, device) in [
+            (5, "root/a/one.txt", 1),
+            (100, "root/mnt/b/two.txt", 2),
+            (1, "root/three.txt", 1),
+            (20, "root/mnt/c/four.txt", 2),
+        ] {
+            let mut record = FileRecord::from_size(size, PathBuf::from(path));
+            record.device = Some(device);
+            builder.add(&record);
+        }
+        assert_eq!(
+            builder.build().devices,
+            vec![
+                DeviceTotal {
+                    device: 1,
+                    files: 2,
+                    total: 6,
+                    path: PathBuf::from("root"),
+                },
+                DeviceTotal {
+                    device: 2,
+                    files: 2,
+                    total: 120,
+                    path: PathBuf::from("root/mnt"),
+                },
+            ]
+        );
+
+        // a single device is not broken down
+        let mut builder = SummaryBuilder::default();
+        let mut record = FileRecord::from_size(1, PathBuf::from("root/a"));
+        record.device = Some(1);
+        builder.add(&record);
+        assert!(builder.build().devices.is_empty());
+    }
+
+    #[test]
+    fn test_summary_builder_roots() {
+        let roots = [PathBuf::from("src"), PathBuf::from("Cargo.toml")];
+        let mut builder = SummaryBuilder::with_roots(&roots);
+        for (size, path) in [(5, "src/a.rs"), (100, "Cargo.toml"), (1, "src/b/c.rs")] {
+            builder.add(&FileRecord::from_size(size, PathBuf::from(path)));
+        }
+        assert_eq!(
+            builder.build().roots,
+            vec![
+                RootTotal {
+                    path: PathBuf::from("src"),
+                    files: 2,
+                    total: 6,
+                },
+                RootTotal {
+                    path: PathBuf::from("Cargo.toml"),
+                    files: 1,
+                    total: 100,
+                },
+            ]
+        );
+
+        // a single root path is not broken down
+        let mut builder = SummaryBuilder::with_roots(&roots[..1]);
+        builder.add(&FileRecord::from_size(1, PathBuf::from("src/a.rs")));
+        assert!(builder.build().roots.is_empty());
+    }
+
+    #[test]
+    fn test_common_ancestor() {
+        assert_eq!(
+            common_ancestor(Path::new("a/b/c"), Path::new("a/b/d")),
+            PathBuf::from("a/b")
+        );
+        assert_eq!(
+            common_ancestor(Path::new("/a/b"), Path::new("/c")),
+            PathBuf::from("/")
+        );
+        assert_eq!(
+            common_ancestor(Path::new("a"), Path::new("b")),
+            PathBuf::new()
+        );
+    }
+
+    #[test]
+    fn test_summary_builder_empty() {
+        let summary = SummaryBuilder::default().build();
+        assert_eq!(summary.files, 0);
+        assert_eq!(summary.total, 0);
+        assert_eq!(summary.mean, 0);
+        assert_eq!(summary.largest, None);
+    }
+}
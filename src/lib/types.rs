@@ -1,6 +1,8 @@
 //! This module provides support for default type definitions that are used to filter paths
 //! with the ignore crate.
-use std::collections::HashSet;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 
 use crate::fuzzy::levenshtein_similarity_ratio;
 use crate::types_default::DEFAULT_TYPES;
@@ -8,13 +10,137 @@ use crate::types_default::DEFAULT_TYPES;
 use anyhow::Result;
 use colored::Colorize;
 use ignore::types::{Types, TypesBuilder};
-use ignore::Error;
+use ignore::{Error, Match};
+
+/// The prefix of the ignore crate type names of the user-defined type aliases with names
+/// that are not alphanumeric.  The ignore crate only supports alphanumeric type names.
+const ENCODED_TYPE_NAME_PREFIX: &str = "siztype";
+
+/// `TypeDefinition` is a struct that represents a user-defined file type alias.
+///
+/// # Fields
+///
+/// * `name`: The type alias name.
+/// * `globs`: The path glob patterns of the type alias.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeDefinition {
+    pub name: String,
+    pub globs: Vec<String>,
+}
+
+/// Parses a `NAME:GLOB[,GLOB...]` user-defined file type alias definition string.
+///
+/// Type alias names include letters, numbers, `-`, and `_` characters.  The glob
+/// patterns are comma-separated, and commas in `{a,b}` alternations are preserved.
+/// A definition with the name of a built-in type alias adds the glob patterns to the
+/// built-in type alias.
+///
+/// # Examples
+///
+/// ```
+/// use siz::types::parse_type_definition;
+///
+/// let definition = parse_type_definition("ml-weights:*.safetensors,*.ckpt,*.pt").unwrap();
+/// assert_eq!(definition.name, "ml-weights");
+/// assert_eq!(definition.globs, vec!["*.safetensors", "*.ckpt", "*.pt"]);
+/// assert!(parse_type_definition("ml weights:*.pt").is_err());
+/// ```
+pub fn parse_type_definition(definition: &str) -> Result<TypeDefinition, String> {
+    let Some((name, globs)) = definition.split_once(':') else {
+        return Err(format!(
+            "invalid type definition '{}': expected NAME:GLOB, e.g., 'ml-weights:*.safetensors,*.pt'",
+            definition
+        ));
+    };
+    let name = name.trim();
+    if name.is_empty()
+        || name == "all"
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "invalid type name '{}': type names include letters, numbers, '-', and '_' and cannot be 'all'",
+            name
+        ));
+    }
+    let globs = split_globs(globs);
+    if globs.is_empty() {
+        return Err(format!(
+            "invalid type definition '{}': expected at least one glob pattern",
+            definition
+        ));
+    }
+    Ok(TypeDefinition {
+        name: name.to_string(),
+        globs,
+    })
+}
+
+/// Splits comma-separated glob patterns, preserving the commas in `{a,b}` alternations.
+fn split_globs(globs: &str) -> Vec<String> {
+    let mut parts: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut depth: usize = 0;
+    for c in globs.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => (),
+        }
+        current.push(c);
+    }
+    parts.push(current);
+    parts
+        .into_iter()
+        .map(|part| part.trim().to_string())
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+/// Returns the ignore crate type name of a type alias name.  Names that are not
+/// alphanumeric are hex-encoded with a prefix.
+fn type_key(name: &str) -> Cow<'_, str> {
+    if name != "all"
+        && !name.is_empty()
+        && name.chars().all(char::is_alphanumeric)
+        && !name.starts_with(ENCODED_TYPE_NAME_PREFIX)
+    {
+        Cow::Borrowed(name)
+    } else {
+        let hex: String = name.bytes().map(|b| format!("{:02x}", b)).collect();
+        Cow::Owned(format!("{}{}", ENCODED_TYPE_NAME_PREFIX, hex))
+    }
+}
+
+/// Returns the type alias name of an ignore crate type name.  This is the inverse
+/// of `type_key`.
+fn type_name(key: &str) -> Cow<'_, str> {
+    let decoded = key.strip_prefix(ENCODED_TYPE_NAME_PREFIX).and_then(|hex| {
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| {
+                hex.get(i..i + 2)
+                    .and_then(|b| u8::from_str_radix(b, 16).ok())
+            })
+            .collect::<Option<Vec<u8>>>()?;
+        String::from_utf8(bytes).ok()
+    });
+    match decoded {
+        Some(name) => Cow::Owned(name),
+        None => Cow::Borrowed(key),
+    }
+}
 
 /// A builder for creating the `ignore::types::Types` struct that is used
 /// to filter files based on default path glob patterns.
 ///
-/// The `SizTypesBuilder` struct provides methods for loading the default types,
-/// defining type filters based on user input, and generating printable string
+/// The `SizTypesBuilder` struct provides methods for loading the default types and
+/// user-defined types, defining type filters based on user input, and generating printable string
 /// representations of the type names and glob patterns. It also supports approximate
 /// string matching for type name suggestions when an unsupported type value is requested.
 ///
@@ -30,6 +156,8 @@ use ignore::Error;
 /// ```
 pub struct SizTypesBuilder {
     builder: TypesBuilder,
+    user_types: Vec<TypeDefinition>,
+    negated_types: Vec<String>,
 }
 
 impl Default for SizTypesBuilder {
@@ -60,9 +188,51 @@ impl SizTypesBuilder {
     pub fn new() -> Self {
         Self {
             builder: TypesBuilder::new(),
+            user_types: Vec::new(),
+            negated_types: Vec::new(),
         }
     }
 
+    /// Adds user-defined type aliases to the default types.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use siz::types::{parse_type_definition, SizTypesBuilder};
+    ///
+    /// let mut builder = SizTypesBuilder::new();
+    /// builder.add_user_types(&[parse_type_definition("ml-weights:*.safetensors").unwrap()]);
+    ///
+    /// let types = builder.filter_types(&vec![String::from("ml-weights")]).unwrap();
+    /// assert!(types.matched("model.safetensors", false).is_whitelist());
+    /// ```
+    pub fn add_user_types(&mut self, user_types: &[TypeDefinition]) -> &mut Self {
+        self.user_types.extend_from_slice(user_types);
+        self
+    }
+
+    /// Adds type names that exclude the matching paths from the filter.
+    ///
+    /// Negated types are combined with the selected types of `filter_types`.  When a
+    /// path matches a selected type and a negated type, the path is excluded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use siz::types::SizTypesBuilder;
+    ///
+    /// let mut builder = SizTypesBuilder::new();
+    /// builder.negate_types(&[String::from("lock")]);
+    ///
+    /// let types = builder.filter_types(&vec![]).unwrap();
+    /// assert!(types.matched("Cargo.lock", false).is_ignore());
+    /// assert!(types.matched("main.rs", false).is_none());
+    /// ```
+    pub fn negate_types(&mut self, types: &[String]) -> &mut Self {
+        self.negated_types.extend_from_slice(types);
+        self
+    }
+
     fn add_type_defaults(&mut self) {
         for &(names, exts) in DEFAULT_TYPES {
             for name in names {
@@ -71,6 +241,28 @@ impl SizTypesBuilder {
                 }
             }
         }
+        for definition in &self.user_types {
+            for glob in &definition.globs {
+                self.builder
+                    .add(&type_key(&definition.name), glob)
+                    .expect("should never fail");
+            }
+        }
+    }
+
+    /// Returns the (names, path glob patterns) of the default types and the
+    /// user-defined types.
+    fn type_definitions(&self) -> Vec<(Vec<&str>, Vec<&str>)> {
+        DEFAULT_TYPES
+            .iter()
+            .map(|&(names, exts)| (names.to_vec(), exts.to_vec()))
+            .chain(self.user_types.iter().map(|definition| {
+                (
+                    vec![definition.name.as_str()],
+                    definition.globs.iter().map(String::as_str).collect(),
+                )
+            }))
+            .collect()
     }
 
     fn get_approximate_match_types(&mut self, needle: &String) -> Vec<Vec<String>> {
@@ -81,9 +273,9 @@ impl SizTypesBuilder {
         let similarity_ratio_threshold = 0.75;
         let mut matches = HashSet::new();
         let mut lev_ratios: Vec<(f64, String)> = vec![];
-        for ty in DEFAULT_TYPES.iter() {
-            let ty_names = ty.0;
-            let ty_exts = ty.1;
+        for ty in self.type_definitions().iter() {
+            let ty_names = &ty.0;
+            let ty_exts = &ty.1;
             let mut temp_ratios: Vec<f64> = vec![];
             // extension string matches
             for ext in ty_exts {
@@ -139,9 +331,9 @@ impl SizTypesBuilder {
     /// Defines the active filter types by string input.
     ///
     /// This method takes a vector of type names and returns a `Result` containing
-    /// an `ignore::types::Types` struct defined with the type name data. If an
-    /// unsupported type is requested, the method performs approximate string matching
-    /// to suggest alternative types.
+    /// an `ignore::types::Types` struct defined with the type name data and the negated
+    /// type names of `negate_types`. If an unsupported type is requested or negated, the
+    /// method performs approximate string matching to suggest alternative types.
     ///
     /// # Arguments
     ///
@@ -177,12 +369,16 @@ impl SizTypesBuilder {
     pub fn filter_types(&mut self, types: &Vec<String>) -> Result<Types> {
         self.add_type_defaults();
         for t in types {
-            self.builder.select(t);
+            self.builder.select(&type_key(t));
+        }
+        // negations are defined after the selections so that they take precedence
+        for t in &self.negated_types {
+            self.builder.negate(&type_key(t));
         }
         match self.builder.build() {
             Ok(types) => Ok(types),
             Err(err) => match err {
-                Error::UnrecognizedFileType(ref name) => {
+                Error::UnrecognizedFileType(ref key) => {
                     // user requested a type that is not supported
                     // let's approximate string match on requested type name for
                     // suggestions to return to the user.  This runs matches against
@@ -190,6 +386,8 @@ impl SizTypesBuilder {
                     // matches against the type names and path glob strings without the "*" or "."
                     // chars using the Levenshtein distance algorithm.  Then we format the
                     // user output with the best matches.
+                    let name = &type_name(key).into_owned();
+                    let err = format!("unrecognized file type: {}", name);
                     let suggestions = self.get_approximate_match_types(name);
                     let matches = &suggestions[0];
                     let matches_string = format!(
@@ -228,14 +426,138 @@ impl SizTypesBuilder {
     }
 }
 
-/// Generates a printable representation of the default type names and glob patterns.
+/// A classifier that maps file paths to the built-in and user-defined file type alias names.
+///
+/// The `TypeClassifier` struct matches file names against the path glob patterns of
+/// all default types and user-defined types.  Paths are classified with the primary
+/// (first) type name of a type definition.
+///
+/// # Examples
+///
+/// ```
+/// use siz::types::TypeClassifier;
+///
+/// let classifier = TypeClassifier::new().unwrap();
+/// assert_eq!(classifier.classify("src/main.rs"), Some("rust"));
+/// assert_eq!(classifier.classify("src/main.bogus"), None);
+/// ```
+pub struct TypeClassifier {
+    types: Types,
+    user_type_names: HashMap<String, String>,
+}
+
+impl TypeClassifier {
+    /// Creates a new `TypeClassifier` instance with all default types.
+    ///
+    /// When a path matches the glob patterns of more than one default type, the
+    /// type with the lexicographically greatest primary name takes precedence.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the type matcher cannot be built.
+    pub fn new() -> Result<Self> {
+        Self::with_user_types(&[])
+    }
+
+    /// Creates a new `TypeClassifier` instance with all default types and the
+    /// user-defined types.
+    ///
+    /// User-defined types take precedence over the default types, and a later
+    /// user-defined type takes precedence over an earlier one, when a path matches
+    /// the glob patterns of more than one type.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the type matcher cannot be built.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use siz::types::{parse_type_definition, TypeClassifier};
+    ///
+    /// let user_types = [parse_type_definition("ml-weights:*.safetensors").unwrap()];
+    /// let classifier = TypeClassifier::with_user_types(&user_types).unwrap();
+    /// assert_eq!(classifier.classify("model.safetensors"), Some("ml-weights"));
+    /// ```
+    pub fn with_user_types(user_types: &[TypeDefinition]) -> Result<Self> {
+        let mut builder = TypesBuilder::new();
+        let mut default_keys: Vec<&str> = Vec::with_capacity(DEFAULT_TYPES.len());
+        for &(names, exts) in DEFAULT_TYPES {
+            for ext in exts {
+                builder.add(names[0], ext)?;
+            }
+            default_keys.push(names[0]);
+        }
+        let mut user_type_names: HashMap<String, String> = HashMap::new();
+        let mut user_keys: Vec<Cow<str>> = Vec::with_capacity(user_types.len());
+        for definition in user_types {
+            let key = type_key(&definition.name);
+            for glob in &definition.globs {
+                builder.add(&key, glob)?;
+            }
+            if key != definition.name {
+                user_type_names.insert(key.to_string(), definition.name.clone());
+            }
+            if !user_keys.contains(&key) {
+                user_keys.push(key);
+            }
+        }
+        // select the types one at a time rather than with the "all" selection,
+        // which does not define a stable precedence for paths that match the glob
+        // patterns of more than one type. The last selected type that matches a
+        // path takes precedence, so the default types are selected in sorted
+        // order before the user-defined types in definition order.
+        default_keys.retain(|key| !user_keys.iter().any(|user_key| user_key == key));
+        default_keys.sort_unstable();
+        for key in default_keys {
+            builder.select(key);
+        }
+        for key in &user_keys {
+            builder.select(key);
+        }
+        Ok(Self {
+            types: builder.build()?,
+            user_type_names,
+        })
+    }
+
+    /// Returns the primary type name that matches the file name of `path`, or `None`
+    /// when the path does not match a type definition.
+    #[inline(always)]
+    pub fn classify<P: AsRef<Path>>(&self, path: P) -> Option<&str> {
+        // the matched glob only borrows from the matcher for the duration of this
+        // call, return the type name borrowed from the sorted matcher definitions
+        let definitions = self.types.definitions();
+        match self.types.matched(path, false) {
+            Match::Whitelist(glob) => {
+                let name = glob.file_type_def()?.name();
+                let name = definitions
+                    .binary_search_by(|def| def.name().cmp(name))
+                    .ok()
+                    .map(|i| definitions[i].name())?;
+                // user-defined type names that are not alphanumeric are encoded
+                match self.user_type_names.get(name) {
+                    Some(user_type_name) => Some(user_type_name.as_str()),
+                    None => Some(name),
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Generates a printable representation of the default and user-defined type names and
+/// glob patterns.
 ///
 /// This method returns a string containing the type names and associated glob patterns.
+/// The glob patterns of user-defined types with the name of a default type are listed with
+/// the default type, and the other user-defined types are listed after the default types.
 ///
 /// # Arguments
 ///
 /// * `color` - A boolean indicating whether to include ANSI color formatting in the
-/// output string.
+///   output string.
+/// * `user_types` - A slice of the user-defined type definitions.
 ///
 /// # Returns
 ///
@@ -244,26 +566,53 @@ impl SizTypesBuilder {
 /// # Examples
 ///
 /// ```
-/// use siz::types::get_printable_types;
+/// use siz::types::{get_printable_types, parse_type_definition};
 ///
-/// let printable_types = get_printable_types(true);
+/// let user_types = [parse_type_definition("ml-weights:*.safetensors").unwrap()];
+/// let printable_types = get_printable_types(true, &user_types);
 /// println!("{}", printable_types);
 /// ```
-pub fn get_printable_types(color: bool) -> String {
-    let mut types_string = String::new();
+pub fn get_printable_types(color: bool, user_types: &[TypeDefinition]) -> String {
+    // the user-defined glob patterns of each type name, in definition order
+    let mut user_globs: Vec<(&str, Vec<&str>)> = Vec::new();
+    for definition in user_types {
+        let globs = definition.globs.iter().map(String::as_str);
+        match user_globs
+            .iter_mut()
+            .find(|(name, _)| *name == definition.name)
+        {
+            Some((_, user_type_globs)) => user_type_globs.extend(globs),
+            None => user_globs.push((definition.name.as_str(), globs.collect())),
+        }
+    }
+    let mut type_lines: Vec<(&str, Vec<&str>)> = Vec::new();
     for &(names, exts) in DEFAULT_TYPES {
         for name in names {
-            if color {
-                types_string += &format!("{}:", name.blue().bold());
-            } else {
-                types_string += &format!("{}:", name);
-            }
-            for ext in exts {
-                types_string += &format!(" {}", ext);
+            let mut globs = exts.to_vec();
+            if let Some(i) = user_globs
+                .iter()
+                .position(|(user_name, _)| user_name == name)
+            {
+                globs.extend(user_globs.remove(i).1);
             }
-            types_string += "\n";
+            type_lines.push((name, globs));
         }
     }
+    user_globs.sort_by(|a, b| a.0.cmp(b.0));
+    type_lines.extend(user_globs);
+
+    let mut types_string = String::new();
+    for (name, exts) in type_lines {
+        if color {
+            types_string += &format!("{}:", name.blue().bold());
+        } else {
+            types_string += &format!("{}:", name);
+        }
+        for ext in exts {
+            types_string += &format!(" {}", ext);
+        }
+        types_string += "\n";
+    }
     if types_string.ends_with('\n') {
         types_string = types_string.strip_suffix('\n').unwrap().to_string();
     }
@@ -281,7 +630,7 @@ mod tests {
     fn test_filter_types_single() -> Result<()> {
         let mut stb = SizTypesBuilder::new();
 
-        assert!(stb.builder.definitions().len() == 0);
+        assert!(stb.builder.definitions().is_empty());
 
         let matcher = stb.filter_types(&vec![String::from("rust")])?;
 
@@ -297,7 +646,7 @@ mod tests {
     fn test_filter_types_multiple() -> Result<()> {
         let mut stb = SizTypesBuilder::new();
 
-        assert!(stb.builder.definitions().len() == 0);
+        assert!(stb.builder.definitions().is_empty());
 
         let matcher = stb.filter_types(&vec![String::from("rust"), String::from("py")])?;
 
@@ -313,7 +662,7 @@ mod tests {
     fn test_filter_types_missing_type() -> Result<()> {
         let mut stb = SizTypesBuilder::new();
 
-        assert!(stb.builder.definitions().len() == 0);
+        assert!(stb.builder.definitions().is_empty());
 
         // unsupported types raise an error
         assert!(stb.filter_types(&vec![String::from("bogus")]).is_err());
@@ -325,6 +674,65 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_filter_types_negated() -> Result<()> {
+        let mut stb = SizTypesBuilder::new();
+        stb.negate_types(&[String::from("lock"), String::from("minified")]);
+
+        let matcher = stb.filter_types(&vec![])?;
+
+        // negated types are ignored
+        assert!(matcher.matched("Cargo.lock", false).is_ignore());
+        assert!(matcher.matched("app.min.js", false).is_ignore());
+        // and other paths are not filtered when no types are selected
+        assert!(matcher.matched("app.js", false).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_types_selected_and_negated() -> Result<()> {
+        let mut stb = SizTypesBuilder::new();
+        stb.negate_types(&[String::from("minified")]);
+
+        let matcher = stb.filter_types(&vec![String::from("js")])?;
+
+        assert!(matcher.matched("app.js", false).is_whitelist());
+        // negated types take precedence over the selected types
+        assert!(matcher.matched("app.min.js", false).is_ignore());
+        assert!(matcher.matched("foo.py", false).is_ignore());
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_types_user_types_overlap() -> Result<()> {
+        let user_types = [parse_type_definition("mine:*.rs").unwrap()];
+
+        let mut stb = SizTypesBuilder::new();
+        stb.add_user_types(&user_types);
+        let matcher = stb.filter_types(&vec![String::from("mine")])?;
+        assert!(matcher.matched("main.rs", false).is_whitelist());
+        assert!(matcher.matched("main.py", false).is_ignore());
+
+        let mut stb = SizTypesBuilder::new();
+        stb.add_user_types(&user_types)
+            .negate_types(&[String::from("mine")]);
+        let matcher = stb.filter_types(&vec![])?;
+        assert!(matcher.matched("main.rs", false).is_ignore());
+        assert!(!matcher.matched("main.py", false).is_ignore());
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_types_negated_missing_type() {
+        let mut stb = SizTypesBuilder::new();
+        stb.negate_types(&[String::from("lok")]);
+
+        let err = stb.filter_types(&vec![]).unwrap_err().to_string();
+
+        assert!(err.starts_with("unrecognized file type: lok\n"));
+        assert!(err.contains("lock"));
+    }
+
     #[test]
     fn test_get_approximate_match_types_exact_match() {
         let mut stb = SizTypesBuilder::new();
@@ -353,9 +761,140 @@ mod tests {
         assert_eq!(result[1], vec!["xml".to_string(), "xls".to_string()]);
     }
 
+    #[test]
+    fn test_type_classifier() -> Result<()> {
+        let classifier = TypeClassifier::new()?;
+
+        assert_eq!(classifier.classify("foo.rs"), Some("rust"));
+        assert_eq!(classifier.classify("a/b/foo.py"), Some("py"));
+        // the primary type name is used for multi-name definitions
+        assert_eq!(classifier.classify("foo.md"), Some("markdown"));
+        // overlapping glob patterns resolve to the same type on every run
+        assert_eq!(classifier.classify("foo.tar.gz"), Some("tar"));
+        assert_eq!(classifier.classify("foo.bogus"), None);
+        assert_eq!(classifier.classify("foo"), None);
+        Ok(())
+    }
+
     #[test]
     fn test_get_printable_types() {
-        let _ = get_printable_types(false);
-        let _ = get_printable_types(true);
+        let _ = get_printable_types(false, &[]);
+        let _ = get_printable_types(true, &[]);
+    }
+
+    #[test]
+    fn test_get_printable_types_user_types() -> Result<()> {
+        let user_types = [
+            parse_type_definition("ml-weights:*.safetensors,*.ckpt").unwrap(),
+            parse_type_definition("rust:*.rs.in").unwrap(),
+            parse_type_definition("ml-weights:*.pt").unwrap(),
+        ];
+        let types_string = get_printable_types(false, &user_types);
+
+        assert!(types_string.contains("\nrust: *.rs *.rs.in\n"));
+        assert!(types_string.ends_with("\nml-weights: *.safetensors *.ckpt *.pt"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_type_definition() {
+        assert_eq!(
+            parse_type_definition("ml_weights2: *.{ckpt,pt} , *.bin"),
+            Ok(TypeDefinition {
+                name: "ml_weights2".to_string(),
+                globs: vec!["*.{ckpt,pt}".to_string(), "*.bin".to_string()],
+            })
+        );
+        assert!(parse_type_definition("ml-weights").is_err());
+        assert!(parse_type_definition("all:*.pt").is_err());
+        assert!(parse_type_definition(":*.pt").is_err());
+        assert!(parse_type_definition("ml.weights:*.pt").is_err());
+        assert!(parse_type_definition("ml-weights:,").is_err());
+    }
+
+    #[test]
+    fn test_type_key_round_trip() {
+        assert_eq!(type_key("rust"), "rust");
+        assert_ne!(type_key("ml-weights"), "ml-weights");
+        for name in [
+            "rust",
+            "ml-weights",
+            "all",
+            "siztype",
+            "siztype6d6c",
+            "ml_weights",
+        ] {
+            let key = type_key(name);
+            assert!(key.chars().all(char::is_alphanumeric));
+            assert_eq!(type_name(&key), name);
+        }
+    }
+
+    #[test]
+    fn test_filter_types_user_types() -> Result<()> {
+        let mut stb = SizTypesBuilder::new();
+        stb.add_user_types(&[
+            parse_type_definition("ml-weights:*.safetensors,*.ckpt").unwrap(),
+            parse_type_definition("rust:*.rs.in").unwrap(),
+        ]);
+
+        let matcher = stb.filter_types(&vec![String::from("ml-weights"), String::from("rust")])?;
+
+        assert!(matcher.matched("model.safetensors", false).is_whitelist());
+        assert!(matcher.matched("model.ckpt", false).is_whitelist());
+        assert!(matcher.matched("build.rs.in", false).is_whitelist());
+        assert!(matcher.matched("foo.rs", false).is_whitelist());
+        assert!(matcher.matched("foo.py", false).is_ignore());
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_types_user_type_suggestions() {
+        let mut stb = SizTypesBuilder::new();
+        stb.add_user_types(&[parse_type_definition("ml-weights:*.safetensors").unwrap()]);
+
+        let err = stb
+            .filter_types(&vec![String::from("ml-weight")])
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.starts_with("unrecognized file type: ml-weight\n"));
+        assert!(err.contains("ml-weights"));
+    }
+
+    #[test]
+    fn test_type_classifier_user_types() -> Result<()> {
+        let classifier = TypeClassifier::with_user_types(&[
+            parse_type_definition("ml-weights:*.safetensors").unwrap(),
+            parse_type_definition("models:*.onnx").unwrap(),
+        ])?;
+
+        assert_eq!(
+            classifier.classify("a/model.safetensors"),
+            Some("ml-weights")
+        );
+        assert_eq!(classifier.classify("model.onnx"), Some("models"));
+        assert_eq!(classifier.classify("foo.rs"), Some("rust"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_classifier_user_types_precedence() -> Result<()> {
+        // user-defined types take precedence over the default types whatever
+        // their names, including encoded names
+        for name in ["mine", "zz", "a", "my-rust"] {
+            let definition = parse_type_definition(&format!("{}:*.rs", name)).unwrap();
+            let classifier = TypeClassifier::with_user_types(&[definition])?;
+            assert_eq!(classifier.classify("src/main.rs"), Some(name));
+            assert_eq!(classifier.classify("main.py"), Some("py"));
+        }
+
+        // a later user-defined type takes precedence over an earlier one
+        let classifier = TypeClassifier::with_user_types(&[
+            parse_type_definition("zz:*.rs").unwrap(),
+            parse_type_definition("mine:*.rs").unwrap(),
+        ])?;
+        assert_eq!(classifier.classify("main.rs"), Some("mine"));
+        Ok(())
     }
 }
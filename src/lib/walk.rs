@@ -1,11 +1,224 @@
 //! This module provides support for walking the file system.
-use anyhow::{Error, Result};
-use ignore::{overrides::OverrideBuilder, WalkBuilder};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Error, Result};
+use ignore::{
+    overrides::OverrideBuilder, types::Types, ParallelVisitor, ParallelVisitorBuilder, WalkBuilder,
+    WalkState,
+};
 
 use crate::args::Args;
-use crate::stdstreams::format_print_file;
+use crate::exclude::ExcludeMatcher;
+use crate::filter::EntryFilter;
+use crate::ignored::IgnoreClassifier;
+use crate::record::RecordBuilder;
+use crate::select::RecordHeap;
+use crate::stdstreams::ReportWriter;
 use crate::types::SizTypesBuilder;
 
+/// The chained `ignore::Walk` instances of the path arguments, in argument order.
+type WalkChain = std::iter::Flatten<std::vec::IntoIter<ignore::Walk>>;
+
+/// Returns the `paths` without the repeated paths and the paths that are nested in
+/// another path, in argument order.  The files of overlapping paths would otherwise be
+/// reported more than once.
+///
+/// The paths are compared by their canonical paths, e.g., `src` and `./src/lib` overlap.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+///
+/// use siz::walk::walk_roots;
+///
+/// let paths = ["src/lib", "benches", "./src", "benches"].map(PathBuf::from);
+/// assert_eq!(walk_roots(&paths), ["benches", "./src"].map(PathBuf::from));
+/// ```
+pub fn walk_roots(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let canonical: Vec<PathBuf> = paths
+        .iter()
+        .map(|path| path.canonicalize().unwrap_or_else(|_| path.clone()))
+        .collect();
+    paths
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| {
+            !canonical.iter().enumerate().any(|(j, other)| {
+                if canonical[*i] == *other {
+                    j < *i
+                } else {
+                    canonical[*i].starts_with(other)
+                }
+            })
+        })
+        .map(|(_, path)| path.clone())
+        .collect()
+}
+
+/// Returns an `ignore::WalkBuilder` for each path argument that is configured with the
+/// command line arguments that are shared by the sequential and parallel walkers.
+///
+/// Each path is walked with its own walk builder so that the glob, exclude, and one file
+/// system filters are relative to the path that contains a file.  The ignore file
+/// classifier of the only ignored mode is shared by the walk builders of all paths.
+fn build_walk_builders(args: &Args) -> Result<Vec<WalkBuilder>> {
+    let paths = walk_roots(&args.paths);
+    let ignored = if args.only_ignored {
+        let args = Args {
+            paths: paths.clone(),
+            ..args.clone()
+        };
+        Some(Arc::new(IgnoreClassifier::new(&args)?))
+    } else {
+        None
+    };
+    paths
+        .into_iter()
+        .map(|path| {
+            build_walk_builder(
+                &Args {
+                    paths: vec![path.clone()],
+                    ..args.clone()
+                },
+                ignored.clone(),
+            )
+        })
+        .collect()
+}
+
+/// Returns an `ignore::WalkBuilder` of the first path argument that is configured with
+/// the command line arguments that are shared by the sequential and parallel walkers,
+/// and with the `ignored` ignore file classifier of the only ignored mode.
+fn build_walk_builder(args: &Args, ignored: Option<Arc<IgnoreClassifier>>) -> Result<WalkBuilder> {
+    // we index the first path here, and the exclude matcher and the entry
+    // filter below index it too, because build_walk_builders passes the
+    // arguments of each path
+    let root = &args.paths[0];
+    let mut walker = WalkBuilder::new(root);
+    walker
+        .hidden(!args.hidden)
+        .skip_stdout(true)
+        .require_git(true)
+        .git_global(false)
+        .git_exclude(false)
+        .follow_links(args.follow);
+
+    // respect the .gitignore and .ignore file rules unless they are disabled
+    // Note: The walk does not apply the ignore file rules in only ignored
+    //       mode and in the by-ignore report. The files are classified
+    //       with the ignore file rules instead, in the entry filter below
+    //       or in the report.
+    if args.no_ignore || args.only_ignored || args.by_ignore {
+        walker.ignore(false).git_ignore(false);
+    } else if args.no_ignore_vcs {
+        walker.git_ignore(false);
+    }
+    if !args.only_ignored && !args.by_ignore {
+        for path in &args.ignore_file {
+            if let Some(err) = walker.add_ignore(path) {
+                return Err(Error::new(err)).context("unable to read ignore file");
+            }
+        }
+    }
+    // the depth option defines a report display depth rather than
+    // a traversal depth in directory size aggregation mode. The
+    // full tree must be walked to calculate the directory totals.
+    if !args.dirs {
+        walker.max_depth(args.depth);
+    }
+
+    // filter files on user-defined globs
+    let has_globs = args.glob.as_ref().is_some_and(|globs| !globs.is_empty());
+    if has_globs {
+        let mut ovrb = OverrideBuilder::new(root);
+        for glob in args.glob.as_ref().unwrap() {
+            ovrb.add(glob)?;
+        }
+        let ovr = ovrb.build()?;
+        // add the overrides to the walker
+        walker.overrides(ovr);
+    }
+
+    // filter files on user-defined default types and negated types
+    // Note: The walker does not consult the type matcher for files that
+    //       match a glob override. The type matcher is applied in the
+    //       entry filter below when globs are also defined so that a file
+    //       must match both the globs and the types.
+    let mut glob_types: Option<Types> = None;
+    if args.default_type.is_some() || args.type_not.is_some() {
+        let mut types_builder = SizTypesBuilder::new();
+        types_builder.add_user_types(&args.type_add);
+        if let Some(negated_types) = &args.type_not {
+            types_builder.negate_types(negated_types);
+        }
+        let user_types = args.default_type.clone().unwrap_or_default();
+        let types = types_builder.filter_types(&user_types)?;
+        if has_globs {
+            glob_types = Some(types);
+        } else {
+            walker.types(types);
+        }
+    }
+
+    // exclude paths on user-defined exclude patterns
+    // Note: The exclude patterns are applied in the entry filter below
+    //       rather than as glob overrides so that they only remove
+    //       paths. Excluded directories are not traversed.
+    let excludes = ExcludeMatcher::from_args(args)?;
+
+    // filter files on user-defined file metadata filters
+    // Note: directories pass the filter so that the walk descends
+    //       into them, unless they are mount points in one file
+    //       system mode.
+    let entry_filter = EntryFilter::from_args(args)?;
+    if entry_filter.is_active() || glob_types.is_some() || excludes.is_some() || ignored.is_some() {
+        walker.filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+            if let Some(excludes) = &excludes {
+                if excludes.is_excluded(entry.path(), is_dir) {
+                    return false;
+                }
+            }
+            if let Some(ignored) = &ignored {
+                if !is_dir && !ignored.is_ignored(entry.path(), false) {
+                    return false;
+                }
+            }
+            if let Some(types) = &glob_types {
+                if !is_dir && types.matched(entry.path(), false).is_ignore() {
+                    return false;
+                }
+            }
+            if !entry_filter.is_active() {
+                return true;
+            }
+            match entry.metadata() {
+                Ok(metadata) => {
+                    let matches = entry_filter.matches(&metadata);
+                    // directories only fail the filter when they are on another
+                    // device in one file system mode
+                    if !matches && metadata.is_dir() {
+                        eprintln!("Skipped mount point: {}", entry.path().display());
+                    }
+                    matches
+                }
+                // let the walker report metadata errors on the entry
+                Err(_) => true,
+            }
+        });
+    }
+
+    // the device IDs that the entry filter uses are only available on Unix
+    #[cfg(not(unix))]
+    if args.one_file_system {
+        walker.same_file_system(true);
+    }
+
+    Ok(walker)
+}
+
 /// `Walker` is a struct that encapsulates the functionality of walking the file system.
 ///
 /// It uses the `ignore::Walk` struct from the `ignore` crate to perform the file system walk.
@@ -14,7 +227,8 @@ use crate::types::SizTypesBuilder;
 ///
 /// # Fields
 ///
-/// * `walker`: An `ignore::Walk` instance that performs the file system walk.
+/// * `walker`: The chained `ignore::Walk` instances that walk the path arguments in
+///   argument order.
 ///
 /// # Usage
 ///
@@ -34,7 +248,7 @@ use crate::types::SizTypesBuilder;
 /// let walker = Walker::new(&args).unwrap();
 /// ```
 pub struct Walker {
-    walker: ignore::Walk,
+    walker: WalkChain,
 }
 
 impl Walker {
@@ -62,57 +276,17 @@ impl Walker {
     /// let walker = Walker::new(&args).unwrap();
     /// ```
     pub fn new(args: &Args) -> Result<Self> {
-        // we unwrap Option here because we know it is Some(PathBuf) from
-        // the arg parsing logic in main.rs
-        let mut binding = WalkBuilder::new(args.path.as_ref().unwrap());
-        let walker = binding
-            .hidden(!args.hidden)
-            .skip_stdout(true)
-            .require_git(true)
-            .max_depth(args.depth)
-            .git_global(false)
-            .git_exclude(false)
-            .follow_links(args.follow);
-
-        // sort by file path string
-        if args.name {
-            walker.sort_by_file_path(|a, b| a.cmp(b));
-        }
-
-        // filter files on user-defined default types
-        // Note: This is not compatible with the glob option defined below.
-        //       We do not allow both options to be used together at arg parse
-        //       time.
-        match &args.default_type {
-            Some(user_types) => {
-                let mut types_builder = SizTypesBuilder::new();
-                walker.types(types_builder.filter_types(user_types)?);
+        let mut walkers = Vec::new();
+        for mut walker in build_walk_builders(args)? {
+            // sort by file path string
+            if args.name {
+                walker.sort_by_file_path(|a, b| a.cmp(b));
             }
-            None => (),
-        }
-
-        // filter files on user-defined globs
-        // Note: This is not compatible with the default_type option defined above.
-        //       We do not allow both options to be used together at arg parse time.
-        match &args.glob {
-            Some(globs) => {
-                if !globs.is_empty() {
-                    // we unwrap Option here because we know it is Some(PathBuf) from
-                    // the arg parsing logic in main.rs
-                    let mut ovrb = OverrideBuilder::new(args.path.as_ref().unwrap());
-                    for glob in globs {
-                        ovrb.add(glob)?;
-                    }
-                    let ovr = ovrb.build()?;
-                    // add the overrides to the walker
-                    walker.overrides(ovr);
-                }
-            }
-            None => (),
+            walkers.push(walker.build());
         }
 
         Ok(Self {
-            walker: walker.build(),
+            walker: walkers.into_iter().flatten(),
         })
     }
 }
@@ -146,7 +320,8 @@ impl From<FileWalker> for Walker {
 ///
 /// # Fields
 ///
-/// * `walker`: An `ignore::Walk` instance that performs the file system walk.
+/// * `walker`: The chained `ignore::Walk` instances that walk the path arguments in
+///   argument order.
 ///
 /// # Usage
 ///
@@ -166,7 +341,7 @@ impl From<FileWalker> for Walker {
 /// let walker = FileWalker::new(&args).unwrap();
 /// ```
 pub struct FileWalker {
-    walker: ignore::Walk,
+    walker: WalkChain,
 }
 
 impl FileWalker {
@@ -230,7 +405,9 @@ impl From<Walker> for FileWalker {
 ///
 /// # Fields
 ///
-/// * `walker`: An `ignore::WalkParallel` instance that performs the parallel file system walk.
+/// * `walkers`: The `ignore::WalkParallel` instances that walk the path arguments in parallel,
+///   one path after another in argument order.
+/// * `record_builder`: The `RecordBuilder` that creates the report records of the walk.
 ///
 /// # Usage
 ///
@@ -250,7 +427,8 @@ impl From<Walker> for FileWalker {
 /// let walker = ParallelWalker::new(&args).unwrap();
 /// ```
 pub struct ParallelWalker {
-    pub walker: ignore::WalkParallel,
+    pub walkers: Vec<ignore::WalkParallel>,
+    record_builder: RecordBuilder,
 }
 
 impl ParallelWalker {
@@ -278,67 +456,24 @@ impl ParallelWalker {
     /// let walker = ParallelWalker::new(&args).unwrap();
     /// ```
     pub fn new(args: &Args) -> Result<Self> {
-        // we unwrap Option here because we know it is Some(PathBuf) from
-        // the arg parsing logic in main.rs
-        let mut binding = WalkBuilder::new(args.path.as_ref().unwrap());
-        let walker = binding
-            .hidden(!args.hidden)
-            .skip_stdout(true)
-            .require_git(true)
-            .max_depth(args.depth)
-            .git_global(false)
-            .git_exclude(false)
-            .follow_links(args.follow);
-
-        // filter files on user-defined default types
-        // Note: This is not compatible with the glob option defined below.
-        //       We do not allow both options to be used together at arg parse
-        //       time.
-        match &args.default_type {
-            Some(default_types) => {
-                let mut types_builder = SizTypesBuilder::new();
-                let types = types_builder.filter_types(default_types)?;
-                walker.types(types);
-            }
-            None => (),
-        }
-
-        // filter files on user-defined globs
-        // Note: This is not compatible with the default_type option defined above.
-        //       We do not allow both options to be used together at arg parse time.
-        match &args.glob {
-            Some(globs) => {
-                if !globs.is_empty() {
-                    // we unwrap Option here because we know it is Some(PathBuf) from
-                    // the arg parsing logic in main.rs
-                    let mut ovrb = OverrideBuilder::new(args.path.as_ref().unwrap());
-                    for glob in globs {
-                        ovrb.add(glob)?;
-                    }
-                    let ovr = ovrb.build()?;
-                    // add the overrides to the walker
-                    walker.overrides(ovr);
-                }
-            }
-            None => (), // ignore, this should never happen b/c it is prohibited on the CL
-        }
-
         Ok(Self {
-            walker: walker.build_parallel(),
+            walkers: build_walk_builders(args)?
+                .into_iter()
+                .map(|walker| walker.build_parallel())
+                .collect(),
+            record_builder: RecordBuilder::from_args(args),
         })
     }
 
     /// Prints the file sizes and file paths in a parallel file system walk with a `ParallelWalker`
     /// instance to the standard output stream.
     ///
-    /// This method takes a reference to an `Args` struct that contains the command line
-    /// arguments, and two closures that format file sizes in metric and binary units.
+    /// This method takes a reference to a `ReportWriter` struct that writes the file records
+    /// in the output format that is requested on the command line.
     ///
     /// # Parameters
     ///
-    /// * `args`: A reference to an `Args` struct that contains the command line arguments.
-    /// * `metric_size_formatter`: A closure that formats a file size in SI metric units.
-    /// * `binary_size_formatter`: A closure that formats a file size in binary units.
+    /// * `report_writer`: A reference to a `ReportWriter` struct that writes the report records.
     ///
     /// # Returns
     ///
@@ -350,82 +485,197 @@ impl ParallelWalker {
     /// use clap::Parser;
     ///
     /// use siz::args::Args;
-    /// use siz::format::{build_binary_size_formatter, build_metric_size_formatter};
+    /// use siz::stdstreams::ReportWriter;
     /// use siz::walk::ParallelWalker;
     ///
     /// let args = Args::parse_from(vec!["siz", "--parallel", "."]);
     /// let walker = ParallelWalker::new(&args).unwrap();
-    /// let msf = build_metric_size_formatter();
-    /// let bsf = build_binary_size_formatter();
+    /// let report_writer = ReportWriter::new(&args).unwrap();
     /// // print the file sizes in a non-deterministic order with a parallel walker
-    /// walker.print_files(&args, msf, bsf).unwrap();
+    /// walker.print_files(&report_writer).unwrap();
     /// ```
-    pub fn print_files(
-        self,
-        args: &Args,
-        metric_size_formatter: impl Fn(u64) -> String + Send + std::marker::Sync,
-        binary_size_formatter: impl Fn(u64) -> String + Send + std::marker::Sync,
-    ) -> Result<()> {
-        self.walker.run(|| {
-            Box::new(|entry| match entry {
-                Ok(entry) => {
-                    // filter on file paths only, exclude all directory paths
-                    if entry.path().is_file() {
-                        match entry.metadata() {
-                            Ok(metadata) => match format_print_file(
-                                args,
-                                &metadata.len(),
-                                entry.path(),
-                                &metric_size_formatter,
-                                &binary_size_formatter,
-                            ) {
-                                Ok(_) => ignore::WalkState::Continue,
-                                Err(err) => {
-                                    let mut walk_state = ignore::WalkState::Quit;
-                                    let aerr = anyhow::Error::new(err);
-                                    let mut broken_pipe_error = false;
-                                    for cause in aerr.chain() {
-                                        if let Some(ioerr) = cause.downcast_ref::<std::io::Error>()
-                                        {
-                                            if ioerr.kind() == std::io::ErrorKind::BrokenPipe {
-                                                walk_state = ignore::WalkState::Continue;
-                                                broken_pipe_error = true;
-                                                break;
+    pub fn print_files(self, report_writer: &ReportWriter) -> Result<()> {
+        let record_builder = &self.record_builder;
+        // the path arguments are walked one after another
+        for walker in self.walkers {
+            walker.run(|| {
+                Box::new(|entry| match entry {
+                    Ok(entry) => {
+                        // filter on file paths only, exclude all directory paths
+                        if entry.path().is_file() {
+                            match entry.metadata() {
+                                // suppressed duplicate hard links are not written
+                                Ok(metadata) => match record_builder
+                                    .build(entry.into_path(), &metadata)
+                                    .map_or(Ok(()), |record| report_writer.write_record(&record))
+                                {
+                                    Ok(_) => ignore::WalkState::Continue,
+                                    Err(err) => {
+                                        let mut walk_state = ignore::WalkState::Quit;
+                                        let aerr = anyhow::Error::new(err);
+                                        let mut broken_pipe_error = false;
+                                        for cause in aerr.chain() {
+                                            if let Some(ioerr) =
+                                                cause.downcast_ref::<std::io::Error>()
+                                            {
+                                                if ioerr.kind() == std::io::ErrorKind::BrokenPipe {
+                                                    walk_state = ignore::WalkState::Continue;
+                                                    broken_pipe_error = true;
+                                                    break;
+                                                }
                                             }
                                         }
+                                        if !broken_pipe_error {
+                                            eprintln!(
+                                                "Error printing to standard output: {}",
+                                                aerr
+                                            );
+                                        }
+                                        walk_state
                                     }
-                                    if !broken_pipe_error {
-                                        eprintln!("Error printing to standard output: {}", aerr);
-                                    }
-                                    walk_state
+                                },
+                                Err(e) => {
+                                    eprintln!("Error reading metadata: {}", e);
+                                    ignore::WalkState::Quit
                                 }
-                            },
-                            Err(e) => {
-                                eprintln!("Error reading metadata: {}", e);
-                                ignore::WalkState::Quit
                             }
+                        } else {
+                            // is a directory, not a file
+                            // continue
+                            ignore::WalkState::Continue
                         }
-                    } else {
-                        // is a directory, not a file
-                        // continue
-                        ignore::WalkState::Continue
                     }
-                }
-                Err(e) => {
-                    eprintln!("Error reading entry: {}", e);
-                    ignore::WalkState::Quit
-                }
-            })
-        });
+                    Err(e) => {
+                        eprintln!("Error reading entry: {}", e);
+                        ignore::WalkState::Quit
+                    }
+                })
+            });
+        }
         Ok(())
     }
+
+    /// Selects the N largest or N smallest files in a parallel file system walk with a
+    /// `ParallelWalker` instance.
+    ///
+    /// Each walker thread keeps a local bounded `RecordHeap` that is merged into the
+    /// returned heap when the thread finishes, so memory use scales with N rather than
+    /// with the number of files in the walk.
+    ///
+    /// # Parameters
+    ///
+    /// * `heap`: An empty `RecordHeap` that defines the size selection.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` that contains the `RecordHeap` with the selected files if the method
+    /// succeeds, or an error if the method fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clap::Parser;
+    ///
+    /// use siz::args::Args;
+    /// use siz::select::{RecordHeap, SizeSelection};
+    /// use siz::walk::ParallelWalker;
+    ///
+    /// let args = Args::parse_from(vec!["siz", "--parallel", "--top", "5", "."]);
+    /// let walker = ParallelWalker::new(&args).unwrap();
+    /// let heap = walker
+    ///     .select_files(RecordHeap::new(SizeSelection::Largest(5)))
+    ///     .unwrap();
+    /// assert!(heap.into_sorted_vec().len() <= 5);
+    /// ```
+    pub fn select_files(self, heap: RecordHeap) -> Result<RecordHeap> {
+        let shared_heap = Mutex::new(heap);
+        for walker in self.walkers {
+            walker.visit(&mut SelectionVisitorBuilder {
+                shared_heap: &shared_heap,
+                record_builder: &self.record_builder,
+            });
+        }
+        match shared_heap.into_inner() {
+            Ok(heap) => Ok(heap),
+            Err(_) => anyhow::bail!("a parallel walker thread panicked during the file selection"),
+        }
+    }
+}
+
+/// Builds the per-thread `SelectionVisitor` instances of a parallel file selection walk.
+struct SelectionVisitorBuilder<'s> {
+    shared_heap: &'s Mutex<RecordHeap>,
+    record_builder: &'s RecordBuilder,
+}
+
+impl<'s> ParallelVisitorBuilder<'s> for SelectionVisitorBuilder<'s> {
+    fn build(&mut self) -> Box<dyn ParallelVisitor + 's> {
+        let selection = match self.shared_heap.lock() {
+            Ok(heap) => heap.selection(),
+            Err(poisoned) => poisoned.get_ref().selection(),
+        };
+        Box::new(SelectionVisitor {
+            heap: Some(RecordHeap::new(selection)),
+            shared_heap: self.shared_heap,
+            record_builder: self.record_builder,
+        })
+    }
+}
+
+/// Selects files into a thread-local `RecordHeap` that is merged into
+/// the shared heap when the walker thread finishes.
+struct SelectionVisitor<'s> {
+    heap: Option<RecordHeap>,
+    shared_heap: &'s Mutex<RecordHeap>,
+    record_builder: &'s RecordBuilder,
+}
+
+impl<'s> ParallelVisitor for SelectionVisitor<'s> {
+    fn visit(&mut self, entry: Result<ignore::DirEntry, ignore::Error>) -> WalkState {
+        match entry {
+            Ok(entry) => {
+                // filter on file paths only, exclude all directory paths
+                if entry.path().is_file() {
+                    match entry.metadata() {
+                        Ok(metadata) => {
+                            if let (Some(heap), Some(record)) = (
+                                self.heap.as_mut(),
+                                self.record_builder.build(entry.into_path(), &metadata),
+                            ) {
+                                heap.push(record);
+                            }
+                            WalkState::Continue
+                        }
+                        Err(e) => {
+                            eprintln!("Error reading metadata: {}", e);
+                            WalkState::Quit
+                        }
+                    }
+                } else {
+                    WalkState::Continue
+                }
+            }
+            Err(e) => {
+                eprintln!("Error reading entry: {}", e);
+                WalkState::Quit
+            }
+        }
+    }
+}
+
+impl<'s> Drop for SelectionVisitor<'s> {
+    fn drop(&mut self) {
+        if let (Some(heap), Ok(mut shared_heap)) = (self.heap.take(), self.shared_heap.lock()) {
+            shared_heap.merge(heap);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use clap::Parser;
-    use ignore::{DirEntry, WalkParallel, WalkState};
+    use ignore::{DirEntry, WalkParallel};
     use pretty_assertions::assert_eq;
     use std::fs::File;
     use std::io::Write;
@@ -500,12 +750,14 @@ mod tests {
 
     fn walk_collect_parallel(prefix: &Path, args: &Args) -> Result<Vec<String>> {
         let mut paths = vec![];
-        for dirent in walk_collect_entries_parallel(ParallelWalker::new(args)?.walker) {
-            let path = dirent.path().strip_prefix(prefix).unwrap();
-            if path.as_os_str().is_empty() {
-                continue;
+        for par_walker in ParallelWalker::new(args)?.walkers {
+            for dirent in walk_collect_entries_parallel(par_walker) {
+                let path = dirent.path().strip_prefix(prefix).unwrap();
+                if path.as_os_str().is_empty() {
+                    continue;
+                }
+                paths.push(normalize_path(path.to_str().unwrap()));
             }
-            paths.push(normalize_path(path.to_str().unwrap()));
         }
         // sort the paths before returning in order
         // in order to be able to test. This represents
@@ -976,6 +1228,472 @@ mod tests {
         Ok(())
     }
 
+    // ================================================
+    // --depth display depth with --dirs aggregation
+    // ================================================
+    #[test]
+    fn test_walker_dirs_depth() -> Result<()> {
+        let td = tmpdir();
+        let td_path = td.path().to_str().unwrap();
+        mkdir_on_path(td.path().join("a/b/c"));
+        write_file(td.path().join("a1.txt"), "");
+        write_file(td.path().join("a/a2.rs"), "");
+        write_file(td.path().join("a/b/a3.py"), "");
+        write_file(td.path().join("a/b/c/a4.md"), "");
+
+        let args = Args::parse_from(vec!["siz", "--dirs", "--depth", "1", td_path]);
+
+        // the depth option does not limit the traversal depth in
+        // directory aggregation mode
+        assert_file_paths_sequential_sorted(
+            td.path(),
+            &args,
+            &["a/a2.rs", "a/b/a3.py", "a/b/c/a4.md", "a1.txt"],
+        )?;
+
+        Ok(())
+    }
+
+    // ================================================
+    // --min-size and --max-size filter options
+    // ================================================
+    #[test]
+    fn test_walker_size_filters() -> Result<()> {
+        let td = tmpdir();
+        let td_path = td.path().to_str().unwrap();
+        mkdir_on_path(td.path().join("a/b"));
+        write_file(td.path().join("empty.txt"), "");
+        write_file(td.path().join("a/ten.txt"), "0123456789");
+        write_file(td.path().join("a/b/thousand.txt"), &"x".repeat(1000));
+        write_file(td.path().join("a/b/kib.txt"), &"x".repeat(1024));
+
+        let args_min = Args::parse_from(vec!["siz", "--min-size", "1kB", td_path]);
+        let args_max = Args::parse_from(vec!["siz", "--max-size", "10", td_path]);
+        let args_range = Args::parse_from(vec![
+            "siz",
+            "--min-size",
+            "1",
+            "--max-size",
+            "1000B",
+            td_path,
+        ]);
+
+        assert_file_paths_sequential_sorted(
+            td.path(),
+            &args_min,
+            &["a/b/kib.txt", "a/b/thousand.txt"],
+        )?;
+        assert_file_paths_sequential_sorted(td.path(), &args_max, &["a/ten.txt", "empty.txt"])?;
+        assert_file_paths_sequential_sorted(
+            td.path(),
+            &args_range,
+            &["a/b/thousand.txt", "a/ten.txt"],
+        )?;
+        // directories are not filtered
+        assert_paths_parallel_sorted(
+            td.path(),
+            &args_min,
+            &["a", "a/b", "a/b/kib.txt", "a/b/thousand.txt"],
+        )?;
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_walker_disk_usage_size_filters() -> Result<()> {
+        let td = tmpdir();
+        let td_path = td.path().to_str().unwrap();
+        write_file(td.path().join("data.txt"), &"x".repeat(1000));
+        // a sparse file with a large apparent size and a small disk usage
+        std::fs::File::create(td.path().join("sparse.img"))?.set_len(64 * 1024 * 1024)?;
+
+        let args_apparent = Args::parse_from(vec!["siz", "--max-size", "1MiB", td_path]);
+        let args_disk_usage =
+            Args::parse_from(vec!["siz", "--disk-usage", "--max-size", "1MiB", td_path]);
+
+        assert_file_paths_sequential_sorted(td.path(), &args_apparent, &["data.txt"])?;
+        assert_file_paths_sequential_sorted(
+            td.path(),
+            &args_disk_usage,
+            &["data.txt", "sparse.img"],
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_walker_time_filters() -> Result<()> {
+        let td = tmpdir();
+        let td_path = td.path().to_str().unwrap();
+        mkdir_on_path(td.path().join("a"));
+        write_file(td.path().join("one.txt"), "1");
+        write_file(td.path().join("a/two.txt"), "22");
+
+        // the files were just written, so they are all newer than one day and
+        // none are older than one day
+        let args_newer = Args::parse_from(vec!["siz", "--newer-than", "1d", td_path]);
+        let args_older = Args::parse_from(vec!["siz", "--older-than", "1d", td_path]);
+        let args_older_date = Args::parse_from(vec!["siz", "--older-than", "2999-01-01", td_path]);
+
+        assert_file_paths_sequential_sorted(td.path(), &args_newer, &["a/two.txt", "one.txt"])?;
+        assert_file_paths_sequential_sorted(td.path(), &args_older, &[])?;
+        assert_file_paths_sequential_sorted(
+            td.path(),
+            &args_older_date,
+            &["a/two.txt", "one.txt"],
+        )?;
+        // directories are not filtered
+        assert_paths_parallel_sorted(td.path(), &args_older, &["a"])?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_walker_type_filters() -> Result<()> {
+        let td = tmpdir();
+        let td_path = td.path().to_str().unwrap();
+        mkdir_on_path(td.path().join("a"));
+        write_file(td.path().join("Cargo.lock"), "1");
+        write_file(td.path().join("app.js"), "1");
+        write_file(td.path().join("a/app.min.js"), "1");
+        write_file(td.path().join("a/main.rs"), "1");
+
+        let args_not = Args::parse_from(vec!["siz", "-T", "lock,minified", td_path]);
+        let args_type_not = Args::parse_from(vec!["siz", "-t", "js", "-T", "minified", td_path]);
+
+        assert_file_paths_sequential_sorted(td.path(), &args_not, &["a/main.rs", "app.js"])?;
+        assert_file_paths_sequential_sorted(td.path(), &args_type_not, &["app.js"])?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_walker_glob_and_type_filters() -> Result<()> {
+        let td = tmpdir();
+        let td_path = td.path().to_str().unwrap();
+        mkdir_on_path(td.path().join("src"));
+        mkdir_on_path(td.path().join("target"));
+        write_file(td.path().join("build.rs"), "1");
+        write_file(td.path().join("README.md"), "1");
+        write_file(td.path().join("src/main.rs"), "1");
+        write_file(td.path().join("src/notes.md"), "1");
+        write_file(td.path().join("target/out.rs"), "1");
+
+        // glob negations exclude files that match the type
+        let args_not = Args::parse_from(vec!["siz", "-t", "rust", "-g", "!target/", td_path]);
+        // glob selections that match files of other types do not bypass the type filter
+        let args_and = Args::parse_from(vec!["siz", "-t", "rust", "-g", "src/**", td_path]);
+        // negated types compose with glob selections
+        let args_type_not =
+            Args::parse_from(vec!["siz", "-T", "markdown", "-g", "src/**", td_path]);
+        let args_parallel = Args::parse_from(vec![
+            "siz",
+            "--parallel",
+            "-t",
+            "rust",
+            "-g",
+            "!target/",
+            td_path,
+        ]);
+
+        assert_file_paths_sequential_sorted(td.path(), &args_not, &["build.rs", "src/main.rs"])?;
+        assert_file_paths_sequential_sorted(td.path(), &args_and, &["src/main.rs"])?;
+        assert_file_paths_sequential_sorted(td.path(), &args_type_not, &["src/main.rs"])?;
+        assert_paths_parallel_sorted(
+            td.path(),
+            &args_parallel,
+            &["build.rs", "src", "src/main.rs"],
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_walker_exclude() -> Result<()> {
+        let td = tmpdir();
+        let td_path = td.path().to_str().unwrap();
+        mkdir_on_path(td.path().join("src"));
+        mkdir_on_path(td.path().join("target/debug"));
+        write_file(td.path().join("build.log"), "1");
+        write_file(td.path().join("README.md"), "1");
+        write_file(td.path().join("src/main.rs"), "1");
+        write_file(td.path().join("target/debug/out.rs"), "1");
+
+        let args_exclude = Args::parse_from(vec![
+            "siz",
+            "--exclude",
+            "target/",
+            "--exclude",
+            "*.log",
+            td_path,
+        ]);
+        // exclude patterns remove paths from the glob selections
+        let args_glob =
+            Args::parse_from(vec!["siz", "-g", "*.rs", "--exclude", "target/", td_path]);
+        let args_parallel =
+            Args::parse_from(vec!["siz", "--parallel", "--exclude", "target/", td_path]);
+
+        assert_file_paths_sequential_sorted(
+            td.path(),
+            &args_exclude,
+            &["README.md", "src/main.rs"],
+        )?;
+        assert_file_paths_sequential_sorted(td.path(), &args_glob, &["src/main.rs"])?;
+        assert_paths_parallel_sorted(
+            td.path(),
+            &args_parallel,
+            &["README.md", "build.log", "src", "src/main.rs"],
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_walker_multiple_paths() -> Result<()> {
+        let td = tmpdir();
+        mkdir_on_path(td.path().join("a/sub"));
+        mkdir_on_path(td.path().join("b/x/sub"));
+        write_file(td.path().join("a/one.rs"), "1");
+        write_file(td.path().join("a/sub/two.rs"), "1");
+        write_file(td.path().join("b/x/sub/three.rs"), "1");
+        write_file(td.path().join("c.txt"), "1");
+        let a_path = td.path().join("a");
+        let b_path = td.path().join("b");
+        let c_path = td.path().join("c.txt");
+        let paths = [
+            b_path.to_str().unwrap(),
+            a_path.to_str().unwrap(),
+            c_path.to_str().unwrap(),
+        ];
+
+        // the paths are walked in argument order, and the anchored
+        // exclude pattern is relative to each path
+        let mut argv = vec!["siz", "--name", "--exclude", "/sub"];
+        argv.extend(paths);
+        let args_exclude = Args::parse_from(argv);
+        let mut argv = vec!["siz", "--parallel"];
+        argv.extend(paths);
+        let args_parallel = Args::parse_from(argv);
+
+        assert_file_paths_sequential(
+            td.path(),
+            &args_exclude,
+            &["b/x/sub/three.rs", "a/one.rs", "c.txt"],
+        )?;
+        assert_paths_parallel_sorted(
+            td.path(),
+            &args_parallel,
+            &[
+                "a",
+                "a/one.rs",
+                "a/sub",
+                "a/sub/two.rs",
+                "b",
+                "b/x",
+                "b/x/sub",
+                "b/x/sub/three.rs",
+                "c.txt",
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_walker_overlapping_paths() -> Result<()> {
+        let td = tmpdir();
+        mkdir_on_path(td.path().join("a/sub"));
+        write_file(td.path().join("a/one.rs"), "1");
+        write_file(td.path().join("a/sub/two.rs"), "1");
+        let a_path = td.path().join("a");
+        let sub_path = td.path().join("a/sub");
+        let dot_path = td.path().join("a/./sub/..");
+        let paths = [
+            sub_path.to_str().unwrap(),
+            a_path.to_str().unwrap(),
+            dot_path.to_str().unwrap(),
+            a_path.to_str().unwrap(),
+        ];
+
+        // the repeated and nested paths are walked once
+        assert_eq!(walk_roots(&paths.map(PathBuf::from)), vec![a_path.clone()]);
+        let mut argv = vec!["siz", "--name"];
+        argv.extend(paths);
+        let args = Args::parse_from(argv);
+        let mut argv = vec!["siz", "--parallel"];
+        argv.extend(paths);
+        let args_parallel = Args::parse_from(argv);
+
+        assert_file_paths_sequential(td.path(), &args, &["a/one.rs", "a/sub/two.rs"])?;
+        assert_paths_parallel_sorted(
+            td.path(),
+            &args_parallel,
+            &["a", "a/one.rs", "a/sub", "a/sub/two.rs"],
+        )?;
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_walker_only_ignored_unreadable_directory() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let td = tmpdir();
+        let td_path = td.path().to_str().unwrap();
+        mkdir_on_path(td.path().join(".git"));
+        write_file(td.path().join(".gitignore"), "*.log\n");
+        write_file(td.path().join("build.log"), "1");
+        mkdir_on_path(td.path().join("locked"));
+        write_file(td.path().join("locked/data.txt"), "1");
+        write_file(td.path().join("locked/debug.log"), "1");
+        let locked = td.path().join("locked");
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o000))?;
+        // privileged users can read the directory regardless of its permissions
+        let readable = std::fs::read_dir(&locked).is_ok();
+
+        // the files of an unreadable tracked directory are not reported as ignored
+        let args = Args::parse_from(vec!["siz", "--only-ignored", td_path]);
+        let got = walk_file_collect_sorted(td.path(), &args);
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755))?;
+        let expected: &[&str] = if readable {
+            &["build.log", "locked/debug.log"]
+        } else {
+            &["build.log"]
+        };
+        assert_eq!(got?, mkpaths(expected));
+        Ok(())
+    }
+
+    #[test]
+    fn test_walker_ignore_files() -> Result<()> {
+        let td = tmpdir();
+        let td_path = td.path().to_str().unwrap();
+        mkdir_on_path(td.path().join(".git"));
+        mkdir_on_path(td.path().join("src"));
+        mkdir_on_path(td.path().join("target/debug"));
+        write_file(td.path().join(".gitignore"), "target/\n");
+        write_file(td.path().join(".ignore"), "*.log\n");
+        write_file(td.path().join("custom-ignore"), "*.md\n");
+        write_file(td.path().join("build.log"), "1");
+        write_file(td.path().join("README.md"), "1");
+        write_file(td.path().join("src/main.rs"), "1");
+        write_file(td.path().join("target/debug/out"), "1");
+        let custom_ignore = td.path().join("custom-ignore");
+        let custom_ignore = custom_ignore.to_str().unwrap();
+
+        let args_default = Args::parse_from(vec!["siz", td_path]);
+        let args_no_ignore = Args::parse_from(vec!["siz", "--no-ignore", td_path]);
+        let args_no_ignore_vcs = Args::parse_from(vec!["siz", "--no-ignore-vcs", td_path]);
+        let args_ignore_file =
+            Args::parse_from(vec!["siz", "--ignore-file", custom_ignore, td_path]);
+        let args_only_ignored = Args::parse_from(vec![
+            "siz",
+            "--only-ignored",
+            "--ignore-file",
+            custom_ignore,
+            td_path,
+        ]);
+        let args_parallel = Args::parse_from(vec!["siz", "--parallel", "--only-ignored", td_path]);
+
+        assert_file_paths_sequential_sorted(
+            td.path(),
+            &args_default,
+            &["README.md", "custom-ignore", "src/main.rs"],
+        )?;
+        assert_file_paths_sequential_sorted(
+            td.path(),
+            &args_no_ignore,
+            &[
+                "README.md",
+                "build.log",
+                "custom-ignore",
+                "src/main.rs",
+                "target/debug/out",
+            ],
+        )?;
+        assert_file_paths_sequential_sorted(
+            td.path(),
+            &args_no_ignore_vcs,
+            &[
+                "README.md",
+                "custom-ignore",
+                "src/main.rs",
+                "target/debug/out",
+            ],
+        )?;
+        assert_file_paths_sequential_sorted(
+            td.path(),
+            &args_ignore_file,
+            &["custom-ignore", "src/main.rs"],
+        )?;
+        assert_file_paths_sequential_sorted(
+            td.path(),
+            &args_only_ignored,
+            &["README.md", "build.log", "target/debug/out"],
+        )?;
+        assert_paths_parallel_sorted(
+            td.path(),
+            &args_parallel,
+            &[
+                "build.log",
+                "src",
+                "target",
+                "target/debug",
+                "target/debug/out",
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    // ================================================
+    // --top and --bottom bounded parallel selection
+    // ================================================
+    #[test]
+    fn test_parallel_walker_select_files() -> Result<()> {
+        use crate::select::SizeSelection;
+
+        let td = tmpdir();
+        let td_path = td.path().to_str().unwrap();
+        mkdir_on_path(td.path().join("a/b"));
+        mkdir_on_path(td.path().join("y/z"));
+        write_file(td.path().join("one.txt"), "1");
+        write_file(td.path().join("a/three.txt"), "333");
+        write_file(td.path().join("a/b/five.txt"), "55555");
+        write_file(td.path().join("y/two.txt"), "22");
+        write_file(td.path().join("y/z/four.txt"), "4444");
+
+        let select = |selection| -> Result<Vec<(u64, String)>> {
+            let args = Args::parse_from(vec!["siz", "--parallel", td_path]);
+            let heap = ParallelWalker::new(&args)?.select_files(RecordHeap::new(selection))?;
+            Ok(heap
+                .into_sorted_vec()
+                .into_iter()
+                .map(|record| {
+                    let path = record.path.strip_prefix(td.path()).unwrap();
+                    (record.size, normalize_path(path.to_str().unwrap()))
+                })
+                .collect())
+        };
+
+        assert_eq!(
+            select(SizeSelection::Largest(2))?,
+            vec![(4, "y/z/four.txt".into()), (5, "a/b/five.txt".into())]
+        );
+        assert_eq!(
+            select(SizeSelection::Smallest(3))?,
+            vec![
+                (1, "one.txt".into()),
+                (2, "y/two.txt".into()),
+                (3, "a/three.txt".into())
+            ]
+        );
+
+        Ok(())
+    }
+
     #[cfg(unix)]
     #[test]
     fn test_walker_symbolic_links() -> Result<()> {
@@ -1,21 +1,29 @@
 //! The siz executable entry point.
 // standard library
+use std::collections::HashMap;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::Path;
 use std::process::ExitCode;
 
 // external libraries
 use anyhow::Result;
-use clap::Parser;
 use colored::Colorize;
 use rayon::prelude::*;
 
 // siz library
-use siz::args::Args;
-use siz::format::{build_binary_size_formatter, build_metric_size_formatter};
-use siz::stdstreams::format_print_file;
-use siz::types::get_printable_types;
-use siz::walk::{FileWalker, ParallelWalker};
+use siz::aggregate::{file_extension, DirectorySizes, GroupSizes, GroupTotal};
+use siz::args::{Args, OutputFormat};
+use siz::budget::{Budget, BUDGET_EXIT_CODE};
+use siz::config::parse_args;
+use siz::duplicates::{find_duplicates, DuplicateSet};
+use siz::filelist::FileList;
+use siz::ignored::{IgnoreClassifier, DEFAULT_CLASS_LARGEST_FILES};
+use siz::record::{FileRecord, RecordBuilder};
+use siz::select::{RecordHeap, SizeSelection};
+use siz::snapshot::{diff_snapshots, FileChange, Snapshot};
+use siz::stdstreams::ReportWriter;
+use siz::types::{get_printable_types, TypeClassifier};
+use siz::walk::{walk_roots, FileWalker, ParallelWalker, Walker};
 
 // main entry point for the siz executable
 fn main() -> ExitCode {
@@ -36,85 +44,372 @@ fn main() -> ExitCode {
 }
 
 fn run() -> Result<ExitCode> {
-    let args = Args::parse();
+    // parse the command line arguments with the siz.toml option defaults
+    let mut args = parse_args()?;
 
     // Short circuit argument handling
     // The block below will return exit status codes without
     // further execution
     if args.list_types {
-        let types_string = get_printable_types(args.color);
+        let types_string = get_printable_types(args.color, &args.type_add);
         println!("{}", types_string);
         return Ok(ExitCode::from(0));
     }
 
+    // The diff option compares two snapshot files or directory
+    // trees and does not use the PATH argument
+    if let Some(diff) = &args.diff {
+        return run_diff(&args, &diff[0], &diff[1]);
+    }
+
     // --------------------------------------------------------------
     // IMPORTANT: must keep the presence of a path definition check
-    // here because we unwrap the Option in other places in the code.
+    // here because we index the first path in other places in the code.
     // --------------------------------------------------------------
-    // Validation: command line path argument
-    match &args.path {
-        Some(path) => {
-            if !path.exists() {
-                anyhow::bail!("path does not exist: {}", path.display());
+    // Validation: command line path arguments
+    // Note: the files-from file list replaces the path arguments
+    if args.paths.is_empty() && args.files_from.is_none() {
+        anyhow::bail!("a file or directory path argument is required. Enter a path at the end of your command.");
+    }
+    for path in &args.paths {
+        if !path.exists() {
+            anyhow::bail!("path does not exist: {}", path.display());
+        }
+    }
+    // overlapping path arguments are reported once
+    args.paths = walk_roots(&args.paths);
+    // NUL-terminated and paths-only records are written in the text output format
+    if (args.print0 || args.paths_only) && args.output != OutputFormat::Text {
+        anyhow::bail!("the print0 and paths-only options require the text output format");
+    }
+    // snapshot files record the file paths relative to a single root path
+    if args.save_snapshot.is_some() && args.paths.len() > 1 {
+        anyhow::bail!("the save-snapshot option does not support multiple path arguments");
+    }
+
+    // instantiate the report writer for the requested output format
+    let report_writer = ReportWriter::new(&args)?;
+    report_writer.write_header()?;
+
+    // creates the report records with the apparent file size or the allocated
+    // disk usage, and with the requested hard link handling
+    let record_builder = RecordBuilder::from_args(&args);
+
+    // collects the report files for the optional snapshot file
+    let mut snapshot = args
+        .save_snapshot
+        .as_ref()
+        .map(|_| Snapshot::new(&args.paths[0]));
+
+    // the exit status code is non-zero when a size budget is exceeded
+    let mut exit_code = ExitCode::from(0);
+
+    if let Some(file_list) = FileList::from_args(&args)? {
+        // file list output, without a directory walk
+        let records =
+            file_list.filter_map(|(path, metadata)| record_builder.build(path, &metadata));
+        let mut v: Vec<FileRecord> = match SizeSelection::from_args(&args) {
+            Some(selection) => {
+                let mut heap = RecordHeap::new(selection);
+                records.for_each(|record| heap.push(record));
+                heap.into_sorted_vec()
             }
+            None => records.collect(),
+        };
+        sort_records(&mut v, &args);
+
+        // Print the report to stdout
+        for record in v.iter() {
+            report_writer.write_record(record)?;
         }
-        None => {
-            anyhow::bail!("a file or directory path argument is required. Enter a path at the end of your command.");
+    } else if let Some(mut budget) = Budget::from_args(&args)? {
+        // size budget assertion output
+        for entry in FileWalker::new(&args)? {
+            let path_entry = entry?;
+            let metadata = path_entry.metadata()?;
+            if let Some(record) = record_builder.build(path_entry.into_path(), &metadata) {
+                budget.add(&record);
+            }
         }
-    }
+        let rule_count = budget.rule_count();
+        let violations = budget.into_violations();
+
+        // Print the report to stdout
+        report_writer.write_budget_violations(&violations, rule_count)?;
+        if !violations.is_empty() {
+            exit_code = ExitCode::from(BUDGET_EXIT_CODE);
+        }
+    } else if args.by_type || args.by_extension {
+        // aggregate file type alias or file extension size total output
+        let classifier = if args.by_type {
+            Some(TypeClassifier::with_user_types(&args.type_add)?)
+        } else {
+            None
+        };
+        let mut group_sizes = GroupSizes::default();
+        for entry in FileWalker::new(&args)? {
+            let path_entry = entry?;
+            let metadata = path_entry.metadata()?;
+            if let Some(record) = record_builder.build(path_entry.into_path(), &metadata) {
+                match &classifier {
+                    Some(classifier) => {
+                        let type_name = classifier.classify(&record.path).unwrap_or("unknown");
+                        group_sizes.add(type_name, &record);
+                    }
+                    None => {
+                        let extension =
+                            file_extension(&record.path, args.extension_parts, args.fold_case);
+                        group_sizes.add(extension.as_deref().unwrap_or("(none)"), &record);
+                    }
+                }
+            }
+        }
+        let grand_total = group_sizes.total();
+        let mut v = group_sizes.into_vec();
+        sort_groups(&mut v, &args);
 
-    // instantiate the human readable size formatters (humansize lib)
-    let metric_size_formatter = build_metric_size_formatter();
-    let binary_size_formatter = build_binary_size_formatter();
+        // Print the report to stdout
+        for group in v.iter() {
+            report_writer.write_group(group, grand_total)?;
+        }
+    } else if args.by_ignore {
+        // ignored and tracked file size total output
+        // Note: the walk does not apply the ignore file rules in this mode
+        let classifier = IgnoreClassifier::new(&args)?;
+        let selection = SizeSelection::Largest(args.top.unwrap_or(DEFAULT_CLASS_LARGEST_FILES));
+        let mut group_sizes = GroupSizes::default();
+        let mut largest_files: HashMap<&str, RecordHeap> = HashMap::new();
+        for entry in FileWalker::new(&args)? {
+            let path_entry = entry?;
+            let metadata = path_entry.metadata()?;
+            if let Some(record) = record_builder.build(path_entry.into_path(), &metadata) {
+                let class_name = classifier.classify(&record.path);
+                group_sizes.add(class_name, &record);
+                largest_files
+                    .entry(class_name)
+                    .or_insert_with(|| RecordHeap::new(selection))
+                    .push(record);
+            }
+        }
+        let grand_total = group_sizes.total();
+        let mut v = group_sizes.into_vec();
+        sort_groups(&mut v, &args);
 
-    if args.parallel {
+        // Print the report to stdout
+        for group in v.iter() {
+            let mut files = largest_files
+                .remove(group.name.as_str())
+                .map(|heap| heap.into_sorted_vec())
+                .unwrap_or_default();
+            sort_records(&mut files, &args);
+            report_writer.write_group_with_files(group, grand_total, &files)?;
+        }
+    } else if args.duplicates {
+        // duplicate file set output
+        let mut v: Vec<FileRecord> = Vec::with_capacity(250);
+        for entry in FileWalker::new(&args)? {
+            let path_entry = entry?;
+            let metadata = path_entry.metadata()?;
+            if let Some(record) = record_builder.build(path_entry.into_path(), &metadata) {
+                v.push(record);
+            }
+        }
+        let mut duplicate_sets = find_duplicates(v);
+        sort_duplicate_sets(&mut duplicate_sets, &args);
+
+        // Print the report to stdout
+        report_writer.write_duplicate_sets(&duplicate_sets)?;
+    } else if let (Some(selection), false) = (SizeSelection::from_args(&args), args.dirs) {
+        // bounded N largest or N smallest file output
+        let heap = if args.parallel {
+            ParallelWalker::new(&args)?.select_files(RecordHeap::new(selection))?
+        } else {
+            let mut heap = RecordHeap::new(selection);
+            for entry in FileWalker::new(&args)? {
+                let path_entry = entry?;
+                let metadata = path_entry.metadata()?;
+                if let Some(record) = record_builder.build(path_entry.into_path(), &metadata) {
+                    heap.push(record);
+                }
+            }
+            heap
+        };
+        let mut v = heap.into_sorted_vec();
+        sort_records(&mut v, &args);
+
+        // Print the report to stdout
+        for record in v.iter() {
+            report_writer.write_record(record)?;
+        }
+    } else if args.parallel {
         // unsorted, parallel directory walk output
-        ParallelWalker::new(&args)?.print_files(
-            &args,
-            &metric_size_formatter,
-            &binary_size_formatter,
-        )?;
-    } else if args.name {
+        ParallelWalker::new(&args)?.print_files(&report_writer)?;
+    } else if args.dirs {
+        // cumulative directory size total output
+        let mut dir_sizes = DirectorySizes::new(&args.paths);
+        for entry in Walker::new(&args)? {
+            let path_entry = entry?;
+            if path_entry.path().is_file() {
+                let metadata = path_entry.metadata()?;
+                // duplicate hard links are not added to the totals in count once mode
+                if let Some(record) = record_builder.build(path_entry.into_path(), &metadata) {
+                    dir_sizes.add_file(&record.path, record.size);
+                }
+            } else if path_entry.path().is_dir() {
+                dir_sizes.add_directory(path_entry.path());
+            }
+        }
+        let dir_records = dir_sizes
+            .into_vec(args.depth)
+            .into_iter()
+            .map(|(dirsize, dirpath)| FileRecord::from_size(dirsize, dirpath));
+        let mut v: Vec<FileRecord> = match SizeSelection::from_args(&args) {
+            Some(selection) => {
+                let mut heap = RecordHeap::new(selection);
+                dir_records.for_each(|record| heap.push(record));
+                heap.into_sorted_vec()
+            }
+            None => dir_records.collect(),
+        };
+        sort_records(&mut v, &args);
+
+        // Print the report to stdout
+        for record in v.iter() {
+            report_writer.write_record(record)?;
+        }
+    } else if args.name && args.paths.len() == 1 {
         // file path name sorted output
+        // Note: The records of multiple path arguments are merged and
+        //       sorted by path name in the default branch below.
         for entry in FileWalker::new(&args)? {
             let path_entry = entry?;
-            format_print_file(
-                &args,
-                &path_entry.metadata()?.len(),
-                path_entry.path(),
-                &metric_size_formatter,
-                &binary_size_formatter,
-            )?;
+            let metadata = path_entry.metadata()?;
+            if let Some(record) = record_builder.build(path_entry.into_path(), &metadata) {
+                if let Some(snapshot) = snapshot.as_mut() {
+                    snapshot.add(&record);
+                }
+                report_writer.write_record(&record)?;
+            }
         }
     } else {
         // default: file size sorted output
-        let mut v: Vec<(u64, PathBuf)> = Vec::with_capacity(250);
+        let mut v: Vec<FileRecord> = Vec::with_capacity(250);
         // recursively walk the directory and fill Vec with
-        // (file size, file path) data
+        // (file size, file path, file metadata) records
         for entry in FileWalker::new(&args)? {
             let path_entry = entry?;
-            v.push((path_entry.metadata()?.len(), path_entry.into_path()));
+            let metadata = path_entry.metadata()?;
+            if let Some(record) = record_builder.build(path_entry.into_path(), &metadata) {
+                v.push(record);
+            }
         }
-
-        // sort the files by size in place, in parallel with rayon lib
-        if args.highlow {
-            // we reverse the sort by swapping the tuple compare order in this line
-            v.par_sort_unstable_by(|(a, b), (c, d)| (c, d).cmp(&(a, b)));
-        } else {
-            v.par_sort_unstable_by(|(a, b), (c, d)| (a, b).cmp(&(c, d)));
+        sort_records(&mut v, &args);
+        if let Some(snapshot) = snapshot.as_mut() {
+            v.iter().for_each(|record| snapshot.add(record));
         }
 
         // Print the report to stdout
-        for (filesize, filepath) in v.iter() {
-            format_print_file(
-                &args,
-                filesize,
-                filepath,
-                &metric_size_formatter,
-                &binary_size_formatter,
-            )?;
+        for record in v.iter() {
+            report_writer.write_record(record)?;
         }
     }
+    report_writer.write_footer()?;
+
+    if let (Some(snapshot), Some(snapshot_path)) = (&snapshot, &args.save_snapshot) {
+        snapshot.save(snapshot_path)?;
+    }
+
     // return zero exit status code if we did not encounter an error
+    // or a size budget violation
+    Ok(exit_code)
+}
+
+// report the file changes between two snapshot files or directory trees
+fn run_diff(args: &Args, old: &Path, new: &Path) -> Result<ExitCode> {
+    let old_snapshot = load_snapshot(old, args)?;
+    let new_snapshot = load_snapshot(new, args)?;
+    let mut changes = diff_snapshots(&old_snapshot, &new_snapshot);
+    sort_changes(&mut changes, args);
+
+    // Print the report to stdout
+    let report_writer = ReportWriter::new(args)?;
+    report_writer.write_header()?;
+    report_writer.write_changes(&changes)?;
+    report_writer.write_footer()?;
+
     Ok(ExitCode::from(0))
 }
+
+// load a snapshot file, or walk a directory tree with the
+// command line filters into a snapshot
+fn load_snapshot(path: &Path, args: &Args) -> Result<Snapshot> {
+    if !path.exists() {
+        anyhow::bail!("path does not exist: {}", path.display());
+    }
+    if !path.is_dir() {
+        return Snapshot::load(path);
+    }
+    let walk_args = Args {
+        paths: vec![path.to_path_buf()],
+        ..args.clone()
+    };
+    let record_builder = RecordBuilder::from_args(&walk_args);
+    let mut snapshot = Snapshot::new(path);
+    for entry in FileWalker::new(&walk_args)? {
+        let path_entry = entry?;
+        let metadata = path_entry.metadata()?;
+        if let Some(record) = record_builder.build(path_entry.into_path(), &metadata) {
+            snapshot.add(&record);
+        }
+    }
+    Ok(snapshot)
+}
+
+// sort the report records by path name or size in place, in parallel with rayon lib
+fn sort_records(v: &mut [FileRecord], args: &Args) {
+    if args.name {
+        v.par_sort_unstable_by(|a, b| a.path.cmp(&b.path));
+    } else if args.highlow {
+        // we reverse the sort by swapping the record compare order in this line
+        v.par_sort_unstable_by(|a, b| (b.size, &b.path).cmp(&(a.size, &a.path)));
+    } else {
+        v.par_sort_unstable_by(|a, b| (a.size, &a.path).cmp(&(b.size, &b.path)));
+    }
+}
+
+// sort the aggregate report groups by name or total size in place
+fn sort_groups(v: &mut [GroupTotal], args: &Args) {
+    if args.name {
+        v.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+    } else if args.highlow {
+        v.sort_unstable_by(|a, b| (b.total, &b.name).cmp(&(a.total, &a.name)));
+    } else {
+        v.sort_unstable_by(|a, b| (a.total, &a.name).cmp(&(b.total, &b.name)));
+    }
+}
+
+// sort the duplicate file sets by path name or file size in place
+fn sort_duplicate_sets(v: &mut [DuplicateSet], args: &Args) {
+    if args.name {
+        v.sort_unstable_by(|a, b| a.paths.cmp(&b.paths));
+    } else if args.highlow {
+        v.sort_unstable_by(|a, b| (b.size, &b.paths).cmp(&(a.size, &a.paths)));
+    } else {
+        v.sort_unstable_by(|a, b| (a.size, &a.paths).cmp(&(b.size, &b.paths)));
+    }
+}
+
+// sort the snapshot diff file changes by path name or absolute size change in place
+fn sort_changes(v: &mut [FileChange], args: &Args) {
+    if args.name {
+        v.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+    } else if args.highlow {
+        v.sort_unstable_by(|a, b| {
+            (b.delta().unsigned_abs(), &b.path).cmp(&(a.delta().unsigned_abs(), &a.path))
+        });
+    } else {
+        v.sort_unstable_by(|a, b| {
+            (a.delta().unsigned_abs(), &a.path).cmp(&(b.delta().unsigned_abs(), &b.path))
+        });
+    }
+}